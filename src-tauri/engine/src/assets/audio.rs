@@ -0,0 +1,395 @@
+// src-tauri/engine/src/assets/audio.rs
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::Arc;
+use lewton::inside_ogg::OggStreamReader;
+use lewton::samples::InterleavedSamples;
+use super::{Asset, AssetError, AudioClip};
+
+// Sources larger than this stream by default instead of decoding up front
+pub const DEFAULT_STREAM_THRESHOLD: usize = 1024 * 1024;
+
+// Decoded samples kept resident per stream (interleaved, all channels)
+pub const DEFAULT_STREAM_BUFFER: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioLoadMode {
+    // Pick by source size against the loader's threshold
+    Auto,
+    Decode,
+    Stream,
+}
+
+pub trait AudioDecoder: Send + Sync {
+    fn sample_rate(&self) -> u32;
+    fn channels(&self) -> u16;
+    
+    // Appends up to `max_samples` interleaved samples, returning how many were
+    // written; 0 means the source is exhausted
+    fn decode(&mut self, out: &mut VecDeque<f32>, max_samples: usize) -> Result<usize, AssetError>;
+    fn rewind(&mut self);
+}
+
+pub fn open_decoder(data: Arc<[u8]>) -> Result<Box<dyn AudioDecoder>, AssetError> {
+    if data.starts_with(b"RIFF") {
+        Ok(Box::new(WavDecoder::new(data)?))
+    } else if data.starts_with(b"OggS") {
+        Ok(Box::new(OggDecoder::new(data)?))
+    } else {
+        Err(AssetError::UnsupportedFormat("audio data is neither RIFF/WAVE nor Ogg".to_string()))
+    }
+}
+
+pub fn decode_clip(data: &[u8]) -> Result<AudioClip, AssetError> {
+    let mut decoder = open_decoder(Arc::from(data))?;
+    let mut samples = VecDeque::new();
+    
+    while decoder.decode(&mut samples, 4096)? > 0 {}
+    
+    Ok(AudioClip {
+        sample_rate: decoder.sample_rate(),
+        channels: decoder.channels(),
+        samples: samples.into(),
+    })
+}
+
+// Either form an audio file can be loaded as, chosen by `AudioLoader`
+pub enum AudioAsset {
+    Clip(AudioClip),
+    Stream(StreamingAudio),
+}
+
+impl AudioAsset {
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, AudioAsset::Stream(_))
+    }
+}
+
+impl Asset for AudioAsset {
+    fn type_name() -> &'static str {
+        "AudioAsset"
+    }
+    
+    fn byte_size(&self) -> usize {
+        match self {
+            AudioAsset::Clip(clip) => clip.byte_size(),
+            AudioAsset::Stream(stream) => stream.buffer_capacity * std::mem::size_of::<f32>(),
+        }
+    }
+}
+
+// Decodes on demand into a bounded ring buffer as the mixer pulls samples,
+// so long music tracks never sit fully decoded in memory
+pub struct StreamingAudio {
+    decoder: Box<dyn AudioDecoder>,
+    buffer: VecDeque<f32>,
+    buffer_capacity: usize,
+    exhausted: bool,
+}
+
+impl StreamingAudio {
+    pub fn new(data: Arc<[u8]>, buffer_capacity: usize) -> Result<Self, AssetError> {
+        let decoder = open_decoder(data)?;
+        let buffer_capacity = buffer_capacity.max(decoder.channels() as usize);
+        
+        let mut stream = Self {
+            decoder,
+            buffer: VecDeque::with_capacity(buffer_capacity),
+            buffer_capacity,
+            exhausted: false,
+        };
+        stream.refill();
+        
+        Ok(stream)
+    }
+    
+    pub fn sample_rate(&self) -> u32 {
+        self.decoder.sample_rate()
+    }
+    
+    pub fn channels(&self) -> u16 {
+        self.decoder.channels()
+    }
+    
+    pub fn read(&mut self, out: &mut [f32]) -> usize {
+        let mut written = 0;
+        
+        while written < out.len() {
+            if self.buffer.is_empty() && self.refill() == 0 {
+                break;
+            }
+            
+            let count = (out.len() - written).min(self.buffer.len());
+            for (slot, sample) in out[written..written + count].iter_mut().zip(self.buffer.drain(..count)) {
+                *slot = sample;
+            }
+            written += count;
+        }
+        
+        written
+    }
+    
+    pub fn buffered_samples(&self) -> usize {
+        self.buffer.len()
+    }
+    
+    pub fn buffer_capacity(&self) -> usize {
+        self.buffer_capacity
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.exhausted && self.buffer.is_empty()
+    }
+    
+    pub fn rewind(&mut self) {
+        self.decoder.rewind();
+        self.buffer.clear();
+        self.exhausted = false;
+        self.refill();
+    }
+    
+    fn refill(&mut self) -> usize {
+        let space = self.buffer_capacity - self.buffer.len();
+        if space == 0 || self.exhausted {
+            return 0;
+        }
+        
+        match self.decoder.decode(&mut self.buffer, space) {
+            Ok(0) => {
+                self.exhausted = true;
+                0
+            }
+            Ok(count) => count,
+            Err(e) => {
+                log::warn!("Audio stream stopped: {}", e);
+                self.exhausted = true;
+                0
+            }
+        }
+    }
+}
+
+impl Asset for StreamingAudio {
+    fn type_name() -> &'static str {
+        "StreamingAudio"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WavSampleFormat {
+    Pcm8,
+    Pcm16,
+    Pcm24,
+    Pcm32,
+    Float32,
+}
+
+impl WavSampleFormat {
+    fn bytes(self) -> usize {
+        match self {
+            WavSampleFormat::Pcm8 => 1,
+            WavSampleFormat::Pcm16 => 2,
+            WavSampleFormat::Pcm24 => 3,
+            WavSampleFormat::Pcm32 | WavSampleFormat::Float32 => 4,
+        }
+    }
+}
+
+struct WavDecoder {
+    data: Arc<[u8]>,
+    sample_rate: u32,
+    channels: u16,
+    format: WavSampleFormat,
+    data_start: usize,
+    data_end: usize,
+    cursor: usize,
+}
+
+impl WavDecoder {
+    fn new(data: Arc<[u8]>) -> Result<Self, AssetError> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Err(AssetError::DecodingError("Missing RIFF/WAVE header".to_string()));
+        }
+        
+        let mut fmt = None;
+        let mut samples = None;
+        let mut pos = 12;
+        
+        // Walk the chunk list; chunks are padded to an even size
+        while pos + 8 <= data.len() {
+            let id = &data[pos..pos + 4];
+            let size = u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]]) as usize;
+            let body_start = pos + 8;
+            let body_end = body_start.saturating_add(size).min(data.len());
+            
+            match id {
+                b"fmt " => fmt = Some(Self::parse_fmt(&data[body_start..body_end])?),
+                b"data" => samples = Some((body_start, body_end)),
+                _ => {}
+            }
+            
+            pos = body_start.saturating_add(size).saturating_add(size & 1);
+        }
+        
+        let (format, channels, sample_rate) = fmt
+            .ok_or_else(|| AssetError::DecodingError("Missing fmt chunk".to_string()))?;
+        let (data_start, data_end) = samples
+            .ok_or_else(|| AssetError::DecodingError("Missing data chunk".to_string()))?;
+        
+        Ok(Self {
+            data,
+            sample_rate,
+            channels,
+            format,
+            data_start,
+            data_end,
+            cursor: data_start,
+        })
+    }
+    
+    fn parse_fmt(body: &[u8]) -> Result<(WavSampleFormat, u16, u32), AssetError> {
+        if body.len() < 16 {
+            return Err(AssetError::DecodingError("Truncated fmt chunk".to_string()));
+        }
+        
+        let mut tag = u16::from_le_bytes([body[0], body[1]]);
+        let channels = u16::from_le_bytes([body[2], body[3]]);
+        let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+        let bits = u16::from_le_bytes([body[14], body[15]]);
+        
+        // WAVE_FORMAT_EXTENSIBLE keeps the real tag at the start of the sub-format GUID
+        if tag == 0xFFFE && body.len() >= 26 {
+            tag = u16::from_le_bytes([body[24], body[25]]);
+        }
+        
+        if channels == 0 {
+            return Err(AssetError::DecodingError("WAV has zero channels".to_string()));
+        }
+        
+        let format = match (tag, bits) {
+            (1, 8) => WavSampleFormat::Pcm8,
+            (1, 16) => WavSampleFormat::Pcm16,
+            (1, 24) => WavSampleFormat::Pcm24,
+            (1, 32) => WavSampleFormat::Pcm32,
+            (3, 32) => WavSampleFormat::Float32,
+            _ => {
+                return Err(AssetError::UnsupportedFormat(
+                    format!("WAV format tag {} with {} bits", tag, bits)
+                ));
+            }
+        };
+        
+        Ok((format, channels, sample_rate))
+    }
+    
+    fn sample_at(&self, offset: usize) -> f32 {
+        let b = &self.data[offset..offset + self.format.bytes()];
+        match self.format {
+            WavSampleFormat::Pcm8 => (b[0] as f32 - 128.0) / 128.0,
+            WavSampleFormat::Pcm16 => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            WavSampleFormat::Pcm24 => {
+                let value = i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8;
+                value as f32 / 8_388_608.0
+            }
+            WavSampleFormat::Pcm32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+            WavSampleFormat::Float32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        }
+    }
+}
+
+impl AudioDecoder for WavDecoder {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+    
+    fn decode(&mut self, out: &mut VecDeque<f32>, max_samples: usize) -> Result<usize, AssetError> {
+        let step = self.format.bytes();
+        let mut written = 0;
+        
+        while written < max_samples && self.cursor + step <= self.data_end {
+            out.push_back(self.sample_at(self.cursor));
+            self.cursor += step;
+            written += 1;
+        }
+        
+        Ok(written)
+    }
+    
+    fn rewind(&mut self) {
+        self.cursor = self.data_start;
+    }
+}
+
+// Ogg Vorbis via lewton. Packets decode to whole blocks, so whatever doesn't
+// fit in the caller's request waits in `pending`
+struct OggDecoder {
+    data: Arc<[u8]>,
+    reader: OggStreamReader<Cursor<Arc<[u8]>>>,
+    pending: VecDeque<f32>,
+}
+
+impl OggDecoder {
+    fn new(data: Arc<[u8]>) -> Result<Self, AssetError> {
+        let reader = Self::open(&data)?;
+        if reader.ident_hdr.audio_channels == 0 {
+            return Err(AssetError::DecodingError("Ogg stream has zero channels".to_string()));
+        }
+        
+        Ok(Self {
+            data,
+            reader,
+            pending: VecDeque::new(),
+        })
+    }
+    
+    fn open(data: &Arc<[u8]>) -> Result<OggStreamReader<Cursor<Arc<[u8]>>>, AssetError> {
+        OggStreamReader::new(Cursor::new(data.clone()))
+            .map_err(|e| AssetError::DecodingError(format!("Ogg Vorbis: {}", e)))
+    }
+}
+
+impl AudioDecoder for OggDecoder {
+    fn sample_rate(&self) -> u32 {
+        self.reader.ident_hdr.audio_sample_rate
+    }
+    
+    fn channels(&self) -> u16 {
+        self.reader.ident_hdr.audio_channels as u16
+    }
+    
+    fn decode(&mut self, out: &mut VecDeque<f32>, max_samples: usize) -> Result<usize, AssetError> {
+        let mut written = 0;
+        
+        while written < max_samples {
+            if self.pending.is_empty() {
+                let packet = self.reader
+                    .read_dec_packet_generic::<InterleavedSamples<f32>>()
+                    .map_err(|e| AssetError::DecodingError(format!("Ogg Vorbis: {}", e)))?;
+                
+                match packet {
+                    Some(packet) => self.pending.extend(packet.samples),
+                    None => break,
+                }
+                continue;
+            }
+            
+            let count = (max_samples - written).min(self.pending.len());
+            out.extend(self.pending.drain(..count));
+            written += count;
+        }
+        
+        Ok(written)
+    }
+    
+    fn rewind(&mut self) {
+        // Headers already parsed once, so reopening can't fail
+        if let Ok(reader) = Self::open(&self.data) {
+            self.reader = reader;
+        }
+        self.pending.clear();
+    }
+}
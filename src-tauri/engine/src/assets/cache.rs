@@ -1,83 +1,207 @@
 // src-tauri/engine/src/assets/cache.rs
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::any::{Any, TypeId};
+use parking_lot::RwLock;
 
+// Used when no budget is configured
+pub const DEFAULT_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+struct CacheEntry {
+    slot: Arc<dyn Any + Send + Sync>,
+    type_id: TypeId,
+    bytes: usize,
+    last_used: AtomicU64,
+}
+
+// Holds assets up to a byte budget, evicting the least recently used ones
+// when an insert goes over. Evicted assets live on in any handles still out
 pub struct AssetCache {
-    assets: HashMap<String, Arc<dyn Any + Send + Sync>>,
-    type_map: HashMap<String, TypeId>,
+    entries: HashMap<String, CacheEntry>,
+    budget: usize,
+    total_bytes: usize,
+    clock: AtomicU64,
+}
+
+impl Default for AssetCache {
+    fn default() -> Self {
+        Self::with_budget(DEFAULT_CACHE_BUDGET)
+    }
 }
 
 impl AssetCache {
     pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_budget(budget: usize) -> Self {
         Self {
-            assets: HashMap::new(),
-            type_map: HashMap::new(),
+            entries: HashMap::new(),
+            budget,
+            total_bytes: 0,
+            clock: AtomicU64::new(0),
         }
     }
     
+    // Inserting over a cached asset of the same type swaps it in place, so
+    // handles already out see the new contents and a bumped version
     pub fn insert<T: Asset>(&mut self, path: String, asset: T) -> AssetHandle<T> {
-        let arc = Arc::new(asset);
-        self.assets.insert(path.clone(), arc.clone() as Arc<dyn Any + Send + Sync>);
-        self.type_map.insert(path.clone(), TypeId::of::<T>());
+        let bytes = asset.byte_size();
         
-        AssetHandle {
-            path,
-            asset: arc,
-        }
+        let handle = match self.get::<T>(&path) {
+            Some(handle) => {
+                *handle.slot.asset.write() = Arc::new(asset);
+                handle.slot.version.fetch_add(1, Ordering::SeqCst);
+                
+                let entry = self.entries.get_mut(&path).expect("entry for cached handle");
+                self.total_bytes = self.total_bytes - entry.bytes + bytes;
+                entry.bytes = bytes;
+                handle
+            }
+            None => {
+                let slot = Arc::new(AssetSlot {
+                    asset: RwLock::new(Arc::new(asset)),
+                    version: AtomicU64::new(0),
+                });
+                
+                self.remove(&path);
+                self.entries.insert(path.clone(), CacheEntry {
+                    slot: slot.clone() as Arc<dyn Any + Send + Sync>,
+                    type_id: TypeId::of::<T>(),
+                    bytes,
+                    last_used: AtomicU64::new(self.tick()),
+                });
+                self.total_bytes += bytes;
+                
+                AssetHandle { path, slot }
+            }
+        };
+        
+        self.evict_to_budget(&handle.path);
+        handle
     }
     
     pub fn get<T: Asset>(&self, path: &str) -> Option<AssetHandle<T>> {
-        // Check type matches
-        let expected_type = TypeId::of::<T>();
-        let actual_type = self.type_map.get(path)?;
+        let entry = self.entries.get(path)?;
         
-        if expected_type != *actual_type {
+        // Check type matches
+        if entry.type_id != TypeId::of::<T>() {
             return None;
         }
         
-        let asset = self.assets.get(path)?;
-        let typed_asset = asset.clone()
-            .downcast::<T>()
+        let typed_slot = entry.slot.clone()
+            .downcast::<AssetSlot<T>>()
             .ok()?;
+        entry.last_used.store(self.tick(), Ordering::Relaxed);
         
         Some(AssetHandle {
             path: path.to_string(),
-            asset: typed_asset,
+            slot: typed_slot,
         })
     }
     
+    pub fn type_of(&self, path: &str) -> Option<TypeId> {
+        self.entries.get(path).map(|entry| entry.type_id)
+    }
+    
     pub fn remove(&mut self, path: &str) -> bool {
-        self.type_map.remove(path);
-        self.assets.remove(path).is_some()
+        match self.entries.remove(path) {
+            Some(entry) => {
+                self.total_bytes -= entry.bytes;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Removes `path` only if no handle to it is left outside the cache
+    pub fn remove_unused(&mut self, path: &str) -> bool {
+        match self.entries.get(path) {
+            Some(entry) if Arc::strong_count(&entry.slot) == 1 => self.remove(path),
+            _ => false,
+        }
     }
     
     pub fn clear(&mut self) {
-        self.assets.clear();
-        self.type_map.clear();
+        self.entries.clear();
+        self.total_bytes = 0;
     }
     
     pub fn size(&self) -> usize {
-        self.assets.len()
+        self.entries.len()
+    }
+    
+    pub fn bytes(&self) -> usize {
+        self.total_bytes
+    }
+    
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+    
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict_to_budget("");
+    }
+    
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+    
+    // `keep` is never evicted, so an asset bigger than the whole budget
+    // still loads; it just pushes everything else out
+    fn evict_to_budget(&mut self, keep: &str) {
+        while self.total_bytes > self.budget {
+            let oldest = self.entries.iter()
+                .filter(|(path, _)| path.as_str() != keep)
+                .min_by_key(|(_, entry)| entry.last_used.load(Ordering::Relaxed))
+                .map(|(path, _)| path.clone());
+            
+            match oldest {
+                Some(path) => {
+                    self.remove(&path);
+                }
+                None => break,
+            }
+        }
     }
 }
 
-#[derive(Clone)]
+// Shared by every handle to one path
+struct AssetSlot<T> {
+    asset: RwLock<Arc<T>>,
+    version: AtomicU64,
+}
+
 pub struct AssetHandle<T: Asset> {
     pub path: String,
-    asset: Arc<T>,
+    slot: Arc<AssetSlot<T>>,
 }
 
 impl<T: Asset> AssetHandle<T> {
-    pub fn get(&self) -> &T {
-        &self.asset
+    // The current contents; hold on to the result to keep a consistent view
+    // across a reload
+    pub fn get(&self) -> Arc<T> {
+        self.slot.asset.read().clone()
+    }
+    
+    // Bumped every time the asset is reloaded
+    pub fn version(&self) -> u64 {
+        self.slot.version.load(Ordering::SeqCst)
+    }
+    
+    // Live handles to this asset, plus one for the cache while it's cached
+    pub fn ref_count(&self) -> usize {
+        Arc::strong_count(&self.slot)
     }
 }
 
-impl<T: Asset> std::ops::Deref for AssetHandle<T> {
-    type Target = T;
-    
-    fn deref(&self) -> &Self::Target {
-        &self.asset
+impl<T: Asset> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            slot: self.slot.clone(),
+        }
     }
-}
\ No newline at end of file
+}
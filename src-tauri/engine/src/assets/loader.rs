@@ -1,14 +1,74 @@
 // src-tauri/engine/src/assets/loader.rs
 use async_trait::async_trait;
-use std::any::Any;
+use serde::Deserialize;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use super::{
+    AudioAsset, AudioLoadMode, StreamingAudio, decode_clip,
+    DEFAULT_STREAM_BUFFER, DEFAULT_STREAM_THRESHOLD,
+};
 
+// Each loader produces exactly one asset type. The manager picks a loader
+// by file extension and the type asked for, then downcasts the result once
 #[async_trait]
 pub trait AssetLoader: Send + Sync {
-    async fn load<T: Asset>(&self, data: &[u8]) -> Result<T, AssetError>;
+    fn asset_type(&self) -> AssetType;
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetType {
+    pub id: TypeId,
+    pub name: &'static str,
+}
+
+impl AssetType {
+    pub fn of<T: Asset>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: T::type_name(),
+        }
+    }
 }
 
-pub trait Asset: Send + Sync + 'static {
+pub trait Asset: AsAny + Send + Sync + 'static {
     fn type_name() -> &'static str where Self: Sized;
+    
+    // Approximate memory held, charged against the cache budget
+    fn byte_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+// Implemented for every asset, so a `Box<dyn Asset>` can be taken back out
+// as its concrete type
+pub trait AsAny {
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send + Sync>;
+    
+    fn asset_type_name(&self) -> &'static str;
+}
+
+impl<T: Asset> AsAny for T {
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send + Sync> {
+        self
+    }
+    
+    fn asset_type_name(&self) -> &'static str {
+        T::type_name()
+    }
+}
+
+impl dyn Asset {
+    pub fn downcast<T: Asset>(self: Box<Self>) -> Result<T, AssetError> {
+        let found = self.asset_type_name();
+        
+        self.into_any()
+            .downcast::<T>()
+            .map(|asset| *asset)
+            .map_err(|_| AssetError::TypeMismatch { expected: T::type_name(), found })
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -27,6 +87,15 @@ pub enum AssetError {
     
     #[error("Asset not found: {0}")]
     NotFound(String),
+    
+    #[error("Asset load cancelled")]
+    Cancelled,
+    
+    #[error("Expected a {expected} asset, but the loader produces {found}")]
+    TypeMismatch { expected: &'static str, found: &'static str },
+    
+    #[error("File watch error: {0}")]
+    Watch(String),
 }
 
 // Texture asset and loader
@@ -49,27 +118,32 @@ impl Asset for Texture {
     fn type_name() -> &'static str {
         "Texture"
     }
+    
+    fn byte_size(&self) -> usize {
+        self.data.len()
+    }
 }
 
 pub struct TextureLoader;
 
 #[async_trait]
 impl AssetLoader for TextureLoader {
-    async fn load<T: Asset>(&self, data: &[u8]) -> Result<T, AssetError> {
-        // This is a hack to work around Rust's type system
-        let texture = self.load_texture(data).await?;
-        
-        // SAFETY: We know T is Texture because of how the AssetManager calls this
-        let any_texture = Box::new(texture) as Box<dyn Any>;
-        match any_texture.downcast::<T>() {
-            Ok(texture) => Ok(*texture),
-            Err(_) => Err(AssetError::DecodingError("Type mismatch".to_string())),
-        }
+    fn asset_type(&self) -> AssetType {
+        AssetType::of::<Texture>()
+    }
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError> {
+        Ok(Box::new(self.load_texture(data).await?))
     }
 }
 
 impl TextureLoader {
     async fn load_texture(&self, data: &[u8]) -> Result<Texture, AssetError> {
+        Self::decode(data)
+    }
+    
+    // Decodes any format the image crate recognizes into RGBA8
+    pub fn decode(data: &[u8]) -> Result<Texture, AssetError> {
         use image::GenericImageView;
         
         let img = image::load_from_memory(data)
@@ -98,25 +172,176 @@ impl Asset for AudioClip {
     fn type_name() -> &'static str {
         "AudioClip"
     }
+    
+    fn byte_size(&self) -> usize {
+        self.samples.len() * std::mem::size_of::<f32>()
+    }
+}
+
+pub struct AudioLoader {
+    pub mode: AudioLoadMode,
+    pub stream_threshold: usize,
+    pub stream_buffer: usize,
+}
+
+impl Default for AudioLoader {
+    fn default() -> Self {
+        Self {
+            mode: AudioLoadMode::Auto,
+            stream_threshold: DEFAULT_STREAM_THRESHOLD,
+            stream_buffer: DEFAULT_STREAM_BUFFER,
+        }
+    }
 }
 
-pub struct AudioLoader;
+impl AudioLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_mode(mut self, mode: AudioLoadMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    
+    pub fn with_stream_threshold(mut self, bytes: usize) -> Self {
+        self.stream_threshold = bytes;
+        self
+    }
+    
+    pub fn should_stream(&self, source_len: usize) -> bool {
+        match self.mode {
+            AudioLoadMode::Auto => source_len > self.stream_threshold,
+            AudioLoadMode::Decode => false,
+            AudioLoadMode::Stream => true,
+        }
+    }
+    
+    // Short SFX decode fully; long tracks stream from the source bytes
+    pub fn load_audio(&self, data: &[u8]) -> Result<AudioAsset, AssetError> {
+        if self.should_stream(data.len()) {
+            Ok(AudioAsset::Stream(StreamingAudio::new(Arc::from(data), self.stream_buffer)?))
+        } else {
+            Ok(AudioAsset::Clip(decode_clip(data)?))
+        }
+    }
+}
 
 #[async_trait]
 impl AssetLoader for AudioLoader {
-    async fn load<T: Asset>(&self, data: &[u8]) -> Result<T, AssetError> {
-        // Simplified - in production you'd use rodio or similar
-        let audio = AudioClip {
-            sample_rate: 44100,
-            channels: 2,
-            samples: vec![],
-        };
+    fn asset_type(&self) -> AssetType {
+        AssetType::of::<AudioAsset>()
+    }
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError> {
+        Ok(Box::new(self.load_audio(data)?))
+    }
+}
+
+// Asking for an `AudioClip` or `StreamingAudio` directly overrides
+// `AudioLoader`'s size heuristic
+pub struct AudioClipLoader;
+
+#[async_trait]
+impl AssetLoader for AudioClipLoader {
+    fn asset_type(&self) -> AssetType {
+        AssetType::of::<AudioClip>()
+    }
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError> {
+        Ok(Box::new(decode_clip(data)?))
+    }
+}
+
+pub struct StreamingAudioLoader {
+    pub stream_buffer: usize,
+}
+
+impl Default for StreamingAudioLoader {
+    fn default() -> Self {
+        Self { stream_buffer: DEFAULT_STREAM_BUFFER }
+    }
+}
+
+#[async_trait]
+impl AssetLoader for StreamingAudioLoader {
+    fn asset_type(&self) -> AssetType {
+        AssetType::of::<StreamingAudio>()
+    }
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError> {
+        Ok(Box::new(StreamingAudio::new(Arc::from(data), self.stream_buffer)?))
+    }
+}
+
+// Bitmap font: glyph rectangles into an atlas texture, described as JSON:
+// {"texture": "fonts/pixel.png", "line_height": 10,
+//  "glyphs": {"A": {"x": 0, "y": 0, "width": 6, "height": 8, "advance": 7}}}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Glyph {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub offset_x: i32,
+    #[serde(default)]
+    pub offset_y: i32,
+    pub advance: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BitmapFont {
+    pub texture: String,
+    pub line_height: u32,
+    pub glyphs: HashMap<char, Glyph>,
+}
+
+impl Asset for BitmapFont {
+    fn type_name() -> &'static str {
+        "BitmapFont"
+    }
+}
+
+impl BitmapFont {
+    pub fn from_json(data: &[u8]) -> Result<Self, AssetError> {
+        let font: BitmapFont = serde_json::from_slice(data)
+            .map_err(|e| AssetError::DecodingError(e.to_string()))?;
         
-        let any_audio = Box::new(audio) as Box<dyn Any>;
-        match any_audio.downcast::<T>() {
-            Ok(audio) => Ok(*audio),
-            Err(_) => Err(AssetError::DecodingError("Type mismatch".to_string())),
+        if font.glyphs.is_empty() {
+            return Err(AssetError::DecodingError("bitmap font has no glyphs".to_string()));
         }
+        
+        Ok(font)
+    }
+    
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c)
+    }
+    
+    // Size in atlas pixels of `text` laid out line by line; characters the
+    // font lacks take no space
+    pub fn measure(&self, text: &str) -> (u32, u32) {
+        let width = text.lines()
+            .map(|line| line.chars().filter_map(|c| self.glyph(c)).map(|g| g.advance).sum())
+            .max()
+            .unwrap_or(0);
+        let lines = text.lines().count().max(1) as u32;
+        
+        (width, lines * self.line_height)
+    }
+}
+
+pub struct BitmapFontLoader;
+
+#[async_trait]
+impl AssetLoader for BitmapFontLoader {
+    fn asset_type(&self) -> AssetType {
+        AssetType::of::<BitmapFont>()
+    }
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError> {
+        Ok(Box::new(BitmapFont::from_json(data)?))
     }
 }
 
@@ -135,19 +360,17 @@ pub struct JsonLoader;
 
 #[async_trait]
 impl AssetLoader for JsonLoader {
-    async fn load<T: Asset>(&self, data: &[u8]) -> Result<T, AssetError> {
+    fn asset_type(&self) -> AssetType {
+        AssetType::of::<JsonAsset>()
+    }
+    
+    async fn load(&self, data: &[u8]) -> Result<Box<dyn Asset>, AssetError> {
         let json_str = std::str::from_utf8(data)
             .map_err(|e| AssetError::DecodingError(e.to_string()))?;
         
         let json_value = serde_json::from_str(json_str)
             .map_err(|e| AssetError::DecodingError(e.to_string()))?;
         
-        let asset = JsonAsset { data: json_value };
-        
-        let any_asset = Box::new(asset) as Box<dyn Any>;
-        match any_asset.downcast::<T>() {
-            Ok(asset) => Ok(*asset),
-            Err(_) => Err(AssetError::DecodingError("Type mismatch".to_string())),
-        }
+        Ok(Box::new(JsonAsset { data: json_value }))
     }
 }
\ No newline at end of file
@@ -1,40 +1,79 @@
 // src-tauri/engine/src/assets/manager.rs
+use std::any::TypeId;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use super::{AssetLoader, AssetCache, Asset, AssetHandle};
+use std::sync::{Arc, Weak};
+use parking_lot::Mutex;
+use tokio::sync::{Mutex as AsyncMutex, RwLock, Semaphore};
+use tokio::task::JoinSet;
+use super::{AssetLoader, AssetCache, Asset, AssetHandle, AssetError, PreloadHandle, PreloadToken};
+use super::{Texture, TextureLoader, AudioClip, AudioClipLoader, AudioLoader, StreamingAudioLoader, JsonAsset, JsonLoader, BitmapFont, BitmapFontLoader};
 
 pub struct AssetManager {
-    loaders: HashMap<String, Box<dyn AssetLoader>>,
+    // Per extension, one loader for each asset type it can be read as
+    loaders: HashMap<String, Vec<Arc<dyn AssetLoader>>>,
     cache: Arc<RwLock<AssetCache>>,
+    in_flight: InFlight,
     base_path: PathBuf,
+    // Preloads started on behalf of each scene, cancelled when it unloads
+    scene_preloads: Mutex<HashMap<String, Vec<PreloadToken>>>,
+    // Files `load_batch` reads and decodes at once
+    max_concurrent_loads: usize,
 }
 
+pub const DEFAULT_MAX_CONCURRENT_LOADS: usize = 8;
+
+// One slot per path being loaded. Callers asking for the same file at once
+// queue on it, so only the first reads and decodes; the rest find its result
+// in the cache. Slots nobody waits on are pruned as new ones open
+type InFlight = Arc<Mutex<HashMap<String, Weak<AsyncMutex<()>>>>>;
+
 impl AssetManager {
     pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
         let mut manager = Self {
             loaders: HashMap::new(),
             cache: Arc::new(RwLock::new(AssetCache::new())),
+            in_flight: InFlight::default(),
             base_path: base_path.as_ref().to_path_buf(),
+            scene_preloads: Mutex::new(HashMap::new()),
+            max_concurrent_loads: DEFAULT_MAX_CONCURRENT_LOADS,
         };
         
         // Register default loaders
         manager.register_loader("png", Box::new(TextureLoader));
         manager.register_loader("jpg", Box::new(TextureLoader));
         manager.register_loader("jpeg", Box::new(TextureLoader));
-        manager.register_loader("ogg", Box::new(AudioLoader));
-        manager.register_loader("wav", Box::new(AudioLoader));
+        for extension in ["ogg", "wav"] {
+            manager.register_loader(extension, Box::new(AudioClipLoader));
+            manager.register_loader(extension, Box::new(StreamingAudioLoader::default()));
+            manager.register_loader(extension, Box::new(AudioLoader::default()));
+        }
         manager.register_loader("json", Box::new(JsonLoader));
+        manager.register_loader("font", Box::new(BitmapFontLoader));
         
         manager
     }
     
+    // Replaces any loader already registered for the same extension and type
     pub fn register_loader(&mut self, extension: &str, loader: Box<dyn AssetLoader>) {
-        self.loaders.insert(extension.to_lowercase(), loader);
+        let loaders = self.loaders.entry(extension.to_lowercase()).or_default();
+        loaders.retain(|l| l.asset_type() != loader.asset_type());
+        loaders.push(Arc::from(loader));
+    }
+    
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+    
+    pub fn set_max_concurrent_loads(&mut self, max: usize) {
+        self.max_concurrent_loads = max.max(1);
     }
     
     pub async fn load<T: Asset>(&self, path: &str) -> Result<AssetHandle<T>, AssetError> {
+        self.load_cancellable(path, None).await
+    }
+    
+    async fn load_cancellable<T: Asset>(&self, path: &str, token: Option<&PreloadToken>) -> Result<AssetHandle<T>, AssetError> {
         // Check cache first
         let cache = self.cache.read().await;
         if let Some(handle) = cache.get::<T>(path) {
@@ -42,32 +81,127 @@ impl AssetManager {
         }
         drop(cache);
         
-        // Load asset
-        let full_path = self.base_path.join(path);
-        let extension = full_path.extension()
-            .and_then(|ext| ext.to_str())
-            .ok_or(AssetError::InvalidPath)?;
+        self.load_job::<T>(path)?.run::<T>(token).await
+    }
+    
+    // Everything a load needs past the cache check, owned so it can run on
+    // a task of its own
+    fn load_job<T: Asset>(&self, path: &str) -> Result<LoadJob, AssetError> {
+        Ok(LoadJob {
+            loader: self.loader_for::<T>(Path::new(path))?,
+            full_path: self.resolve(path)?,
+            path: path.to_string(),
+            cache: self.cache.clone(),
+            in_flight: self.in_flight.clone(),
+        })
+    }
+    
+    // Re-reads a cached asset from disk and swaps it in under every handle
+    // already out. Paths that were never loaded are left alone (`Ok(false)`)
+    pub async fn reload(&self, path: &str) -> Result<bool, AssetError> {
+        let type_id = self.cache.read().await.type_of(path);
         
-        let loader = self.loaders.get(extension)
-            .ok_or(AssetError::UnsupportedFormat(extension.to_string()))?;
+        match type_id {
+            Some(t) if t == TypeId::of::<Texture>() => { self.reload_as::<Texture>(path).await?; }
+            Some(t) if t == TypeId::of::<AudioClip>() => { self.reload_as::<AudioClip>(path).await?; }
+            Some(t) if t == TypeId::of::<JsonAsset>() => { self.reload_as::<JsonAsset>(path).await?; }
+            Some(t) if t == TypeId::of::<BitmapFont>() => { self.reload_as::<BitmapFont>(path).await?; }
+            _ => return Ok(false),
+        }
         
-        let data = tokio::fs::read(&full_path).await
-            .map_err(|e| AssetError::Io(e))?;
+        Ok(true)
+    }
+    
+    // Typed reload, for asset types `reload` doesn't know about
+    pub async fn reload_as<T: Asset>(&self, path: &str) -> Result<AssetHandle<T>, AssetError> {
+        let loader = self.loader_for::<T>(Path::new(path))?;
+        let full_path = self.resolve(path)?;
         
-        let asset = loader.load::<T>(&data).await?;
+        let data = tokio::fs::read(&full_path).await?;
+        let asset = loader.load(&data).await?.downcast::<T>()?;
         
-        // Cache the asset
         let mut cache = self.cache.write().await;
-        let handle = cache.insert(path.to_string(), asset);
+        Ok(cache.insert(path.to_string(), asset))
+    }
+    
+    // Asset paths come from project files, which get shared, so anything
+    // resolving outside the asset root (`..`, absolute paths, symlinks out)
+    // is refused. The file has to exist to be resolved
+    fn resolve(&self, path: &str) -> Result<PathBuf, AssetError> {
+        let root = self.base_path.canonicalize()?;
+        let full_path = root.join(path).canonicalize()?;
         
-        Ok(handle)
+        if !full_path.starts_with(&root) {
+            return Err(AssetError::InvalidPath);
+        }
+        Ok(full_path)
     }
     
+    fn loader_for<T: Asset>(&self, full_path: &Path) -> Result<Arc<dyn AssetLoader>, AssetError> {
+        let extension = full_path.extension()
+            .and_then(|ext| ext.to_str())
+            .ok_or(AssetError::InvalidPath)?;
+        
+        let loaders = self.loaders.get(extension)
+            .ok_or(AssetError::UnsupportedFormat(extension.to_string()))?;
+        
+        // Known extension, wrong type: name what it would have loaded as
+        loaders.iter()
+            .find(|loader| loader.asset_type().id == TypeId::of::<T>())
+            .cloned()
+            .ok_or_else(|| AssetError::TypeMismatch {
+                expected: T::type_name(),
+                found: loaders.last().map_or("nothing", |loader| loader.asset_type().name),
+            })
+    }
+    
+    // Up to `max_concurrent_loads` files are in flight at once, each on a
+    // spawned task so decodes run in parallel; call it from a tokio runtime.
+    // A path listed twice is loaded once; handles come back in the order of
+    // `paths`, and the first failure in that order is the error returned
     pub async fn load_batch<T: Asset>(&self, paths: &[&str]) -> Result<Vec<AssetHandle<T>>, AssetError> {
-        let mut handles = Vec::with_capacity(paths.len());
+        let mut unique: Vec<&str> = paths.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        
+        let mut results: Vec<Option<Result<AssetHandle<T>, AssetError>>> = unique.iter().map(|_| None).collect();
+        let permits = Arc::new(Semaphore::new(self.max_concurrent_loads));
+        let mut loads = JoinSet::new();
+        for (i, path) in unique.iter().enumerate() {
+            let job = match self.load_job::<T>(path) {
+                Ok(job) => job,
+                Err(e) => {
+                    results[i] = Some(Err(e));
+                    continue;
+                }
+            };
+            
+            let permits = permits.clone();
+            loads.spawn(async move {
+                let result = match permits.acquire_owned().await {
+                    Ok(_permit) => job.run::<T>(None).await,
+                    Err(_) => Err(AssetError::Cancelled),
+                };
+                (i, result)
+            });
+        }
+        
+        // A loader that panicked takes the batch down with it
+        while let Some(done) = loads.join_next().await {
+            let (i, result) = done.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+            results[i] = Some(result);
+        }
+        let mut results: Vec<_> = results.into_iter()
+            .map(|result| result.expect("every path was loaded"))
+            .collect();
         
+        let mut handles = Vec::with_capacity(paths.len());
         for path in paths {
-            handles.push(self.load::<T>(path).await?);
+            let i = unique.binary_search(path).expect("every path was loaded");
+            match &results[i] {
+                Ok(handle) => handles.push(handle.clone()),
+                Err(_) => return Err(results.swap_remove(i).err().expect("checked above")),
+            }
         }
         
         Ok(handles)
@@ -77,7 +211,8 @@ impl AssetManager {
         use tokio::fs;
         use tokio_stream::{StreamExt, wrappers::ReadDirStream};
         
-        let full_dir = self.base_path.join(dir);
+        let full_dir = self.resolve(dir)?;
+        let root = self.base_path.canonicalize()?;
         let mut count = 0;
         
         let mut entries = ReadDirStream::new(fs::read_dir(full_dir).await?);
@@ -87,7 +222,7 @@ impl AssetManager {
             let path = entry.path();
             
             if path.is_file() {
-                if let Some(rel_path) = path.strip_prefix(&self.base_path).ok() {
+                if let Ok(rel_path) = path.strip_prefix(&root) {
                     let path_str = rel_path.to_string_lossy();
                     
                     // Determine asset type based on extension
@@ -109,6 +244,68 @@ impl AssetManager {
         Ok(count)
     }
     
+    // Loads every listed asset, stopping at the first file once `token` is
+    // cancelled. Assets finished before that stay cached; nothing half-loaded
+    // is ever inserted
+    pub async fn preload_manifest(&self, paths: &[String], token: PreloadToken) -> Result<usize, AssetError> {
+        let mut count = 0;
+        
+        for path in paths {
+            if token.is_cancelled() {
+                return Err(AssetError::Cancelled);
+            }
+            
+            let extension = Path::new(path).extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.to_lowercase());
+            
+            match extension.as_deref() {
+                Some("png") | Some("jpg") | Some("jpeg") => {
+                    self.load_cancellable::<Texture>(path, Some(&token)).await?;
+                }
+                Some("ogg") | Some("wav") => {
+                    self.load_cancellable::<AudioClip>(path, Some(&token)).await?;
+                }
+                Some("json") => {
+                    self.load_cancellable::<JsonAsset>(path, Some(&token)).await?;
+                }
+                _ => continue,
+            }
+            
+            token.record_loaded();
+            count += 1;
+        }
+        
+        Ok(count)
+    }
+    
+    // Handle for a preload owned by `scene_id`; run it with `preload_manifest`
+    pub fn scene_preload(&self, scene_id: &str) -> PreloadHandle {
+        let handle = PreloadHandle::new();
+        
+        let mut preloads = self.scene_preloads.lock();
+        let tokens = preloads.entry(scene_id.to_string()).or_default();
+        tokens.retain(|t| !t.is_cancelled());
+        tokens.push(handle.token());
+        
+        handle
+    }
+    
+    pub fn unload_scene(&self, scene_id: &str) {
+        if let Some(tokens) = self.scene_preloads.lock().remove(scene_id) {
+            for token in tokens {
+                token.cancel();
+            }
+        }
+    }
+    
+    // Frees a cached asset once nothing else holds a handle to it. Returns
+    // false, leaving the cache alone, while handles are still out
+    pub async fn unload(&self, path: &str) -> bool {
+        let mut cache = self.cache.write().await;
+        cache.remove_unused(path)
+    }
+    
     pub async fn clear_cache(&self) {
         let mut cache = self.cache.write().await;
         cache.clear();
@@ -118,4 +315,65 @@ impl AssetManager {
         let cache = self.cache.read().await;
         cache.size()
     }
-}
\ No newline at end of file
+    
+    pub async fn get_cache_bytes(&self) -> usize {
+        let cache = self.cache.read().await;
+        cache.bytes()
+    }
+    
+    // Shrinking the budget evicts right away
+    pub async fn set_cache_budget(&self, bytes: usize) {
+        let mut cache = self.cache.write().await;
+        cache.set_budget(bytes);
+    }
+}
+
+struct LoadJob {
+    loader: Arc<dyn AssetLoader>,
+    full_path: PathBuf,
+    path: String,
+    cache: Arc<RwLock<AssetCache>>,
+    in_flight: InFlight,
+}
+
+impl LoadJob {
+    async fn run<T: Asset>(self, token: Option<&PreloadToken>) -> Result<AssetHandle<T>, AssetError> {
+        let check = || match token {
+            Some(token) if token.is_cancelled() => Err(AssetError::Cancelled),
+            _ => Ok(()),
+        };
+        
+        let slot = self.slot();
+        let _loading = slot.lock().await;
+        
+        // Whoever held the slot before us may have loaded it already
+        if let Some(handle) = self.cache.read().await.get::<T>(&self.path) {
+            return Ok(handle);
+        }
+        
+        check()?;
+        let data = tokio::fs::read(&self.full_path).await?;
+        
+        check()?;
+        let asset = self.loader.load(&data).await?.downcast::<T>()?;
+        
+        // Cache the asset, unless the preload was called off while decoding
+        let mut cache = self.cache.write().await;
+        check()?;
+        let handle = cache.insert(self.path.clone(), asset);
+        
+        Ok(handle)
+    }
+    
+    fn slot(&self) -> Arc<AsyncMutex<()>> {
+        let mut in_flight = self.in_flight.lock();
+        if let Some(slot) = in_flight.get(&self.path).and_then(Weak::upgrade) {
+            return slot;
+        }
+        
+        in_flight.retain(|_, slot| slot.strong_count() > 0);
+        let slot = Arc::new(AsyncMutex::new(()));
+        in_flight.insert(self.path.clone(), Arc::downgrade(&slot));
+        slot
+    }
+}
@@ -2,7 +2,17 @@
 mod manager;
 mod loader;
 mod cache;
+mod audio;
+mod preload;
+mod pak;
+#[cfg(feature = "hot-reload")]
+mod watcher;
 
 pub use manager::*;
 pub use loader::*;
-pub use cache::*;
\ No newline at end of file
+pub use cache::*;
+pub use audio::*;
+pub use preload::*;
+pub use pak::*;
+#[cfg(feature = "hot-reload")]
+pub use watcher::*;
\ No newline at end of file
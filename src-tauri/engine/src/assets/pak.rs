@@ -0,0 +1,174 @@
+// src-tauri/engine/src/assets/pak.rs
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use super::{AssetError, Texture, TextureLoader};
+
+// Layout, little-endian:
+//   "DPAK", version u32, entry count u32
+//   per entry: id length u16, id, kind u8, offset u64, length u64
+//   the blobs back to back; offsets count from the first one
+pub const PAK_MAGIC: &[u8; 4] = b"DPAK";
+pub const PAK_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PakAssetKind {
+    Texture,
+    Audio,
+    Data,
+}
+
+impl PakAssetKind {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            PakAssetKind::Texture => 0,
+            PakAssetKind::Audio => 1,
+            PakAssetKind::Data => 2,
+        }
+    }
+    
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(PakAssetKind::Texture),
+            1 => Some(PakAssetKind::Audio),
+            2 => Some(PakAssetKind::Data),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PakEntry {
+    kind: PakAssetKind,
+    offset: usize,
+    len: usize,
+}
+
+// Borrowed view over a pak; blobs are sliced out without copying
+pub struct AssetPak<'a> {
+    blobs: &'a [u8],
+    entries: BTreeMap<String, PakEntry>,
+}
+
+impl<'a> AssetPak<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, AssetError> {
+        let mut reader = PakReader { data, pos: 0 };
+        
+        if reader.take(4)? != PAK_MAGIC {
+            return Err(invalid("missing DPAK header"));
+        }
+        let version = reader.u32()?;
+        if version != PAK_VERSION {
+            return Err(invalid(&format!("unsupported version {}", version)));
+        }
+        
+        let count = reader.u32()?;
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let id_len = reader.u16()? as usize;
+            let id = std::str::from_utf8(reader.take(id_len)?)
+                .map_err(|_| invalid("asset id is not UTF-8"))?
+                .to_string();
+            let kind = PakAssetKind::from_byte(reader.u8()?)
+                .ok_or_else(|| invalid(&format!("unknown kind for {}", id)))?;
+            let offset = reader.u64()? as usize;
+            let len = reader.u64()? as usize;
+            
+            entries.insert(id, PakEntry { kind, offset, len });
+        }
+        
+        let blobs = &data[reader.pos..];
+        for (id, entry) in &entries {
+            if entry.offset.checked_add(entry.len).filter(|&end| end <= blobs.len()).is_none() {
+                return Err(invalid(&format!("{} points past the end of the pak", id)));
+            }
+        }
+        
+        Ok(Self { blobs, entries })
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    pub fn get(&self, id: &str) -> Option<&'a [u8]> {
+        let entry = self.entries.get(id)?;
+        Some(&self.blobs[entry.offset..entry.offset + entry.len])
+    }
+    
+    pub fn kind(&self, id: &str) -> Option<PakAssetKind> {
+        self.entries.get(id).map(|entry| entry.kind)
+    }
+    
+    // In id order
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+// Assets unpacked from a game's pak, kept as a world resource. Textures are
+// decoded up front; audio and data stay as the packed bytes
+#[derive(Default)]
+pub struct PackedAssets {
+    pub textures: HashMap<String, Arc<Texture>>,
+    pub blobs: HashMap<String, Vec<u8>>,
+}
+
+impl PackedAssets {
+    pub fn from_pak(pak: &AssetPak) -> Result<Self, AssetError> {
+        let mut assets = Self::default();
+        
+        for id in pak.ids() {
+            let bytes = pak.get(id).unwrap_or_default();
+            match pak.kind(id) {
+                Some(PakAssetKind::Texture) => {
+                    assets.textures.insert(id.to_string(), Arc::new(TextureLoader::decode(bytes)?));
+                }
+                _ => {
+                    assets.blobs.insert(id.to_string(), bytes.to_vec());
+                }
+            }
+        }
+        
+        Ok(assets)
+    }
+}
+
+fn invalid(reason: &str) -> AssetError {
+    AssetError::DecodingError(format!("invalid asset pak: {}", reason))
+}
+
+struct PakReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PakReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AssetError> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| invalid("truncated"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+    
+    fn u8(&mut self) -> Result<u8, AssetError> {
+        Ok(self.take(1)?[0])
+    }
+    
+    fn u16(&mut self) -> Result<u16, AssetError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    
+    fn u32(&mut self) -> Result<u32, AssetError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    
+    fn u64(&mut self) -> Result<u64, AssetError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
@@ -0,0 +1,72 @@
+// src-tauri/engine/src/assets/preload.rs
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+// Shared between a preload and whoever may call it off. Loads check it
+// before reading each file and again before touching the cache
+#[derive(Clone, Default)]
+pub struct PreloadToken {
+    cancelled: Arc<AtomicBool>,
+    loaded: Arc<AtomicUsize>,
+}
+
+impl PreloadToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+    
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+    
+    pub fn loaded(&self) -> usize {
+        self.loaded.load(Ordering::SeqCst)
+    }
+    
+    pub(crate) fn record_loaded(&self) {
+        self.loaded.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// Owns a preload's lifetime: dropping it cancels whatever is still outstanding
+pub struct PreloadHandle {
+    token: PreloadToken,
+}
+
+impl PreloadHandle {
+    pub fn new() -> Self {
+        Self { token: PreloadToken::new() }
+    }
+    
+    pub fn token(&self) -> PreloadToken {
+        self.token.clone()
+    }
+    
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+    
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+    
+    pub fn loaded(&self) -> usize {
+        self.token.loaded()
+    }
+}
+
+impl Default for PreloadHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for PreloadHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
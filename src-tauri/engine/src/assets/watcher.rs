@@ -0,0 +1,52 @@
+// src-tauri/engine/src/assets/watcher.rs
+use std::path::Path;
+use std::sync::Arc;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use super::{AssetError, AssetManager};
+
+// Hot reloading lasts as long as this is kept alive
+pub struct AssetWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl AssetManager {
+    // Reloads cached assets whenever their file under the base path changes.
+    // Must be called from inside a tokio runtime; the reloads are spawned on it
+    pub fn watch(self: &Arc<Self>) -> Result<AssetWatcher, AssetError> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|e| AssetError::Watch(e.to_string()))?;
+        let root = self.base_path().canonicalize()?;
+        let manager = Arc::downgrade(self);
+        let event_root = root.clone();
+        
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else { return };
+            if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                return;
+            }
+            let Some(manager) = manager.upgrade() else { return };
+            
+            for path in &event.paths {
+                let Some(key) = cache_key(&event_root, path) else { continue };
+                let manager = manager.clone();
+                
+                runtime.spawn(async move {
+                    if let Err(e) = manager.reload(&key).await {
+                        log::warn!("Failed to reload {}: {}", key, e);
+                    }
+                });
+            }
+        }).map_err(|e| AssetError::Watch(e.to_string()))?;
+        
+        watcher.watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| AssetError::Watch(e.to_string()))?;
+        
+        Ok(AssetWatcher { _watcher: watcher })
+    }
+}
+
+// Cache keys are the forward-slash paths assets were loaded by
+fn cache_key(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
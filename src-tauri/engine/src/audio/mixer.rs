@@ -0,0 +1,210 @@
+// src-tauri/engine/src/audio/mixer.rs
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::assets::AudioClip;
+use crate::ecs::{EntityId, World};
+use super::AudioSource;
+
+pub type VoiceId = u64;
+
+// Where the mixer's voices actually play. A voice is one playback of one clip
+pub trait AudioBackend: Send + Sync {
+    fn play(&mut self, clip: Arc<AudioClip>, volume: f32) -> VoiceId;
+    fn stop(&mut self, voice: VoiceId);
+    fn set_volume(&mut self, voice: VoiceId, volume: f32);
+    fn is_finished(&self, voice: VoiceId) -> bool;
+    
+    // Called once per mixer tick; backends playing in real time can ignore it
+    fn advance(&mut self, _dt: f32) {}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AudioError {
+    #[error("Audio output unavailable: {0}")]
+    Output(String),
+}
+
+// Plays nothing but keeps each voice alive for its clip's duration of
+// simulated time, so headless engines and tests see the same start, finish
+// and loop behaviour as a real device
+#[derive(Default)]
+pub struct NullAudioBackend {
+    // Seconds left per voice
+    voices: HashMap<VoiceId, f32>,
+    next_voice: VoiceId,
+}
+
+impl AudioBackend for NullAudioBackend {
+    fn play(&mut self, clip: Arc<AudioClip>, _volume: f32) -> VoiceId {
+        let frames = clip.samples.len() / clip.channels.max(1) as usize;
+        let duration = frames as f32 / clip.sample_rate.max(1) as f32;
+        
+        self.next_voice += 1;
+        self.voices.insert(self.next_voice, duration);
+        self.next_voice
+    }
+    
+    fn stop(&mut self, voice: VoiceId) {
+        self.voices.remove(&voice);
+    }
+    
+    fn set_volume(&mut self, _voice: VoiceId, _volume: f32) {}
+    
+    fn is_finished(&self, voice: VoiceId) -> bool {
+        self.voices.get(&voice).filter(|&&remaining| remaining > 0.0).is_none()
+    }
+    
+    fn advance(&mut self, dt: f32) {
+        for remaining in self.voices.values_mut() {
+            *remaining -= dt;
+        }
+    }
+}
+
+// Decoded clips plus the voices playing them, kept as a world resource.
+// `audio_system` starts and stops voices to match each `AudioSource`
+pub struct AudioMixer {
+    backend: Box<dyn AudioBackend>,
+    clips: HashMap<String, Arc<AudioClip>>,
+    sources: HashMap<EntityId, VoiceId>,
+    one_shots: Vec<VoiceId>,
+}
+
+impl AudioMixer {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            clips: HashMap::new(),
+            sources: HashMap::new(),
+            one_shots: Vec::new(),
+        }
+    }
+    
+    pub fn headless() -> Self {
+        Self::new(Box::new(NullAudioBackend::default()))
+    }
+    
+    // The default output device when built with `audio-output` and one can
+    // be opened; silent otherwise
+    pub fn with_default_output() -> Self {
+        #[cfg(feature = "audio-output")]
+        match super::RodioBackend::new() {
+            Ok(backend) => return Self::new(Box::new(backend)),
+            Err(e) => log::warn!("{}, audio will be silent", e),
+        }
+        
+        Self::headless()
+    }
+    
+    pub fn add_clip(&mut self, id: impl Into<String>, clip: AudioClip) {
+        self.clips.insert(id.into(), Arc::new(clip));
+    }
+    
+    pub fn has_clip(&self, id: &str) -> bool {
+        self.clips.contains_key(id)
+    }
+    
+    // Fire-and-forget at full volume; None if the clip isn't loaded
+    pub fn play_one_shot(&mut self, clip_id: &str) -> Option<VoiceId> {
+        let voice = self.start(clip_id, 1.0)?;
+        self.one_shots.push(voice);
+        Some(voice)
+    }
+    
+    // The voice currently playing an entity's `AudioSource`
+    pub fn source_voice(&self, entity: EntityId) -> Option<VoiceId> {
+        self.sources.get(&entity).copied()
+    }
+    
+    pub fn active_voices(&self) -> usize {
+        self.sources.len() + self.one_shots.len()
+    }
+    
+    fn start(&mut self, clip_id: &str, volume: f32) -> Option<VoiceId> {
+        let Some(clip) = self.clips.get(clip_id) else {
+            log::warn!("Audio clip not loaded: {}", clip_id);
+            return None;
+        };
+        Some(self.backend.play(clip.clone(), volume))
+    }
+    
+    fn sync_source(&mut self, entity: EntityId, source: &mut AudioSource) {
+        let voice = self.sources.get(&entity).copied();
+        
+        if !source.playing {
+            if let Some(voice) = voice {
+                self.backend.stop(voice);
+                self.sources.remove(&entity);
+            }
+            return;
+        }
+        
+        match voice {
+            Some(voice) if !self.backend.is_finished(voice) => {
+                self.backend.set_volume(voice, source.volume);
+                return;
+            }
+            Some(voice) => {
+                self.backend.stop(voice);
+                self.sources.remove(&entity);
+                if !source.looping {
+                    source.playing = false;
+                    return;
+                }
+            }
+            None => {}
+        }
+        
+        match self.start(&source.clip_id, source.volume) {
+            Some(voice) => {
+                self.sources.insert(entity, voice);
+            }
+            None => source.playing = false,
+        }
+    }
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self::headless()
+    }
+}
+
+// Brings every `AudioSource` in line with the mixer: starts sources that were
+// switched on, re-queues finished looping ones, and stops voices whose source
+// was switched off, removed or despawned
+pub fn audio_system(world: &mut World, dt: f32) {
+    let Some(mut mixer) = world.remove_resource::<AudioMixer>() else {
+        return;
+    };
+    
+    mixer.backend.advance(dt);
+    
+    let AudioMixer { backend, one_shots, sources, .. } = &mut mixer;
+    one_shots.retain(|&voice| {
+        let finished = backend.is_finished(voice);
+        if finished {
+            backend.stop(voice);
+        }
+        !finished
+    });
+    
+    let ids: Vec<EntityId> = world.query::<&AudioSource>().map(|(id, _)| id).collect();
+    let live: HashSet<EntityId> = ids.iter().copied().collect();
+    sources.retain(|id, voice| {
+        let keep = live.contains(id);
+        if !keep {
+            backend.stop(*voice);
+        }
+        keep
+    });
+    
+    for id in ids {
+        let Some(source) = world.entity(id).and_then(|e| world.get_component_mut::<AudioSource>(e)) else {
+            continue;
+        };
+        mixer.sync_source(id, source);
+    }
+    
+    world.insert_resource(mixer);
+}
@@ -0,0 +1,10 @@
+// src-tauri/engine/src/audio/mod.rs
+mod source;
+mod mixer;
+#[cfg(feature = "audio-output")]
+mod rodio_backend;
+
+pub use source::*;
+pub use mixer::*;
+#[cfg(feature = "audio-output")]
+pub use rodio_backend::*;
@@ -0,0 +1,80 @@
+// src-tauri/engine/src/audio/rodio_backend.rs
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc};
+use rodio::buffer::SamplesBuffer;
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+use crate::assets::AudioClip;
+use super::{AudioBackend, AudioError, VoiceId};
+
+// Plays voices on the default output device, one rodio sink per voice
+pub struct RodioBackend {
+    handle: OutputStreamHandle,
+    sinks: HashMap<VoiceId, Sink>,
+    next_voice: VoiceId,
+    // The output stream isn't Send, so it lives on its own thread until this
+    // sender is dropped
+    _stream_guard: mpsc::Sender<()>,
+}
+
+impl RodioBackend {
+    pub fn new() -> Result<Self, AudioError> {
+        let (handle_tx, handle_rx) = mpsc::channel();
+        let (guard, closed) = mpsc::channel::<()>();
+        
+        std::thread::spawn(move || match OutputStream::try_default() {
+            Ok((_stream, handle)) => {
+                let _ = handle_tx.send(Ok(handle));
+                // Returns once the backend drops its guard
+                let _ = closed.recv();
+            }
+            Err(e) => {
+                let _ = handle_tx.send(Err(e.to_string()));
+            }
+        });
+        
+        let handle = handle_rx.recv()
+            .map_err(|_| AudioError::Output("audio thread exited".to_string()))?
+            .map_err(AudioError::Output)?;
+        
+        Ok(Self {
+            handle,
+            sinks: HashMap::new(),
+            next_voice: 0,
+            _stream_guard: guard,
+        })
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn play(&mut self, clip: Arc<AudioClip>, volume: f32) -> VoiceId {
+        self.next_voice += 1;
+        
+        // A voice whose sink couldn't open just reports finished right away
+        match Sink::try_new(&self.handle) {
+            Ok(sink) => {
+                sink.set_volume(volume);
+                sink.append(SamplesBuffer::new(clip.channels, clip.sample_rate, clip.samples.clone()));
+                self.sinks.insert(self.next_voice, sink);
+            }
+            Err(e) => log::warn!("Couldn't open an audio sink: {}", e),
+        }
+        
+        self.next_voice
+    }
+    
+    fn stop(&mut self, voice: VoiceId) {
+        if let Some(sink) = self.sinks.remove(&voice) {
+            sink.stop();
+        }
+    }
+    
+    fn set_volume(&mut self, voice: VoiceId, volume: f32) {
+        if let Some(sink) = self.sinks.get(&voice) {
+            sink.set_volume(volume);
+        }
+    }
+    
+    fn is_finished(&self, voice: VoiceId) -> bool {
+        self.sinks.get(&voice).filter(|sink| !sink.empty()).is_none()
+    }
+}
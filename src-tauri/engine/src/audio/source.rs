@@ -0,0 +1,39 @@
+// src-tauri/engine/src/audio/source.rs
+use serde::{Deserialize, Serialize};
+use crate::ecs::Component;
+
+// Plays `clip_id` through the world's `AudioMixer` while `playing` is set.
+// A clip that runs out starts over if `looping`, otherwise the mixer clears
+// `playing` so systems can tell it finished
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioSource {
+    pub clip_id: String,
+    pub volume: f32,
+    #[serde(default)]
+    pub looping: bool,
+    #[serde(default)]
+    pub playing: bool,
+}
+
+impl AudioSource {
+    pub fn new(clip_id: impl Into<String>) -> Self {
+        Self {
+            clip_id: clip_id.into(),
+            volume: 1.0,
+            looping: false,
+            playing: false,
+        }
+    }
+    
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+    
+    pub fn playing(mut self) -> Self {
+        self.playing = true;
+        self
+    }
+}
+
+impl Component for AudioSource {}
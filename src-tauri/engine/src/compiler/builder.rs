@@ -1,10 +1,13 @@
 // src-tauri/engine/src/compiler/builder.rs
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::fs;
 use serde::{Deserialize, Serialize};
-use crate::{Project, VisualScript, compile_visual_script};
-use super::CompilerError;
+use crate::{Project, VisualScript, EntityData, GameObject};
+use crate::assets::PakAssetKind;
+use crate::renderer::{FRAME_COMMAND_KINDS, FRAME_SCHEMA_VERSION};
+use super::{CompilerError, PakWriter, compile_visual_script_as, unique_rust_name};
 
 #[derive(Debug, Clone)]
 pub enum BuildTarget {
@@ -34,6 +37,11 @@ pub struct GameCompiler {
     project: Project,
     target: BuildTarget,
     optimize_level: OptimizeLevel,
+    // Seconds since the Unix epoch stamped on every packaged file, making
+    // identical inputs produce byte- and metadata-identical packages
+    reproducible_epoch: Option<u64>,
+    // Textures larger than this on either side are scaled down to fit
+    max_texture_size: Option<u32>,
 }
 
 impl GameCompiler {
@@ -42,6 +50,8 @@ impl GameCompiler {
             project,
             target,
             optimize_level: OptimizeLevel::Release,
+            reproducible_epoch: None,
+            max_texture_size: None,
         }
     }
     
@@ -50,6 +60,17 @@ impl GameCompiler {
         self
     }
     
+    // Usually the project's last commit time, as with SOURCE_DATE_EPOCH
+    pub fn with_reproducible_build(mut self, epoch: u64) -> Self {
+        self.reproducible_epoch = Some(epoch);
+        self
+    }
+    
+    pub fn with_max_texture_size(mut self, max_size: u32) -> Self {
+        self.max_texture_size = Some(max_size);
+        self
+    }
+    
     pub async fn compile(&self) -> Result<BuildResult, CompilerError> {
         let build_dir = self.prepare_build_directory()?;
         
@@ -64,34 +85,79 @@ impl GameCompiler {
         self.generate_entities_code(&build_dir)?;
         
         // Step 4: Process and embed assets
-        let asset_size = self.process_assets(&build_dir).await?;
+        let (asset_size, asset_warnings) = self.process_assets(&build_dir).await?;
         
-        // Step 5: Build the Rust project
-        let executable = self.build_executable(&build_dir).await?;
+        // Step 5: Build the Rust project, unless nothing it's built from changed
+        let executable = self.executable_path(&build_dir);
+        let fingerprint = self.build_fingerprint(&build_dir)?;
+        let fingerprint_path = build_dir.join("cache/build.hash");
+        
+        let warnings_path = build_dir.join("cache/build.warnings");
+        
+        // A skipped build reports the warnings of the build it reuses
+        let warnings = if !executable.exists() || fs::read_to_string(&fingerprint_path).ok().as_deref() != Some(fingerprint.as_str()) {
+            let warnings = self.build_executable(&build_dir).await?;
+            let saved = serde_json::to_vec(&warnings)
+                .map_err(|e| CompilerError::CodeGeneration(e.to_string()))?;
+            fs::write(&warnings_path, saved)?;
+            fs::write(&fingerprint_path, &fingerprint)?;
+            warnings
+        } else {
+            fs::read(&warnings_path).ok()
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+                .unwrap_or_default()
+        };
         
         // Step 6: Create final package
-        let result = self.package_game(&build_dir, executable, asset_size).await?;
+        let mut result = self.package_game(&build_dir, executable, asset_size).await?;
+        result.warnings = asset_warnings;
+        result.warnings.extend(warnings);
         
         Ok(result)
     }
     
+    // Sources and assets are regenerated every build; `cache/` (compiled
+    // scripts) and cargo's `target/` carry over to skip unchanged work
     fn prepare_build_directory(&self) -> Result<PathBuf, CompilerError> {
         let build_dir = Path::new("target/game_builds").join(&self.project.id);
         
-        if build_dir.exists() {
-            fs::remove_dir_all(&build_dir)?;
+        for dir in ["src", "assets"] {
+            let path = build_dir.join(dir);
+            if path.exists() {
+                fs::remove_dir_all(&path)?;
+            }
+            fs::create_dir_all(&path)?;
         }
-        
-        fs::create_dir_all(&build_dir)?;
-        fs::create_dir_all(build_dir.join("src"))?;
-        fs::create_dir_all(build_dir.join("assets"))?;
+        fs::create_dir_all(build_dir.join("cache"))?;
         
         Ok(build_dir)
     }
     
+    // Everything the cargo build depends on
+    fn build_fingerprint(&self, build_dir: &Path) -> Result<String, CompilerError> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(format!("{:?} {:?}", self.target, self.optimize_level).as_bytes());
+        
+        for file in ["Cargo.toml", "src/main.rs", "src/systems.rs", "src/entities.rs", "assets/assets.pak"] {
+            let contents = fs::read(build_dir.join(file))?;
+            hasher.update(&(contents.len() as u64).to_le_bytes());
+            hasher.update(&contents);
+        }
+        
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+    
     fn generate_cargo_toml(&self, build_dir: &Path) -> Result<(), CompilerError> {
         let project_name = self.project.name.to_lowercase().replace(' ', "_");
         
+        // wasm-bindgen works on a cdylib; everything else is a plain binary
+        let crate_section = match self.target {
+            BuildTarget::WebAssembly => format!(
+                "[lib]\nname = \"{}\"\npath = \"src/main.rs\"\ncrate-type = [\"cdylib\"]", project_name
+            ),
+            _ => format!("[[bin]]\nname = \"{}\"\npath = \"src/main.rs\"", project_name),
+        };
+        
         let cargo_toml = format!(r#"[package]
 name = "{}"
 version = "1.0.0"
@@ -113,9 +179,10 @@ panic = "abort"
 inherits = "release"
 opt-level = "z"
 
-[[bin]]
-name = "{}"
-path = "src/main.rs"
+[target.'cfg(target_arch = "wasm32")'.dependencies]
+wasm-bindgen = "0.2"
+
+{}
 "#,
             project_name,
             match self.optimize_level {
@@ -123,7 +190,7 @@ path = "src/main.rs"
                 OptimizeLevel::Release => "3",
                 OptimizeLevel::ReleaseSmall => "\"z\"",
             },
-            project_name
+            crate_section
         );
         
         fs::write(build_dir.join("Cargo.toml"), cargo_toml)?;
@@ -131,7 +198,10 @@ path = "src/main.rs"
     }
     
     fn generate_main_file(&self, build_dir: &Path) -> Result<(), CompilerError> {
-        let main_code = format!(r#"use dream_engine::{{DreamEngine, EngineConfig}};
+        let input_map = serde_json::to_string(&self.project.input_map)
+            .map_err(|e| CompilerError::CodeGeneration(e.to_string()))?;
+        
+        let main_code = format!(r#"use dream_engine::{{DreamEngine, EngineConfig, EngineError, InputMap}};
 
 mod systems;
 mod entities;
@@ -139,56 +209,95 @@ mod entities;
 // Embedded asset data
 const ASSET_DATA: &[u8] = include_bytes!("../assets/assets.pak");
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {{
+// The project's input bindings, as JSON
+const INPUT_MAP: &str = {input_map:?};
+
+fn engine_config() -> EngineConfig {{
     // Initialize engine with project configuration
-    let config = EngineConfig {{
+    EngineConfig {{
         target_fps: 60,
         fixed_timestep: 1.0 / 60.0,
         max_entities: 10000,
-    }};
-    
-    let mut engine = DreamEngine::new(config)?;
+        max_substeps: 5,
+        seed: None,
+    }}
+}}
+
+fn create_engine() -> Result<DreamEngine, EngineError> {{
+    let mut engine = DreamEngine::new(engine_config())?;
+    let input_map = InputMap::from_json(INPUT_MAP).map_err(|e| EngineError::InvalidConfig(e.to_string()))?;
+    engine.input_mut().set_map(input_map);
     
     // Register all compiled systems
     systems::register_systems(engine.systems_mut());
+    engine.systems_mut().resolve_order().map_err(|e| EngineError::SystemError(e.to_string()))?;
     
     // Create initial entities from scenes
-    entities::create_entities(engine.world_mut(), engine.physics_mut());
+    entities::create_entities(&mut engine)?;
     
-    // Load embedded assets
-    // In production, this would deserialize ASSET_DATA
+    // Unpack the embedded assets
+    engine.load_asset_manifest(ASSET_DATA)?;
     
-    // Run the game
-    #[cfg(not(target_arch = "wasm32"))]
-    {{
-        // Native game loop
-        use std::time::{{Duration, Instant}};
-        
-        let mut last_frame = Instant::now();
-        let frame_time = Duration::from_secs_f32(1.0 / config.target_fps as f32);
+    Ok(engine)
+}}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> Result<(), Box<dyn std::error::Error>> {{
+    create_engine()?.run();
+    Ok(())
+}}
+
+// In the browser the JS glue owns the loop (requestAnimationFrame) and calls
+// into the exports below through the wasm-bindgen bindings
+#[cfg(target_arch = "wasm32")]
+mod web {{
+    use std::cell::RefCell;
+    use wasm_bindgen::prelude::*;
+    use dream_engine::{{DreamEngine, Input, InputBinding}};
+    
+    thread_local! {{
+        static ENGINE: RefCell<Option<DreamEngine>> = RefCell::new(None);
+    }}
+    
+    // Runs as soon as the bindings finish instantiating the module
+    #[wasm_bindgen(start)]
+    pub fn dream_start() -> Result<(), JsValue> {{
+        let engine = super::create_engine().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        ENGINE.with(|slot| *slot.borrow_mut() = Some(engine));
+        Ok(())
+    }}
+    
+    #[wasm_bindgen]
+    pub fn dream_key(code: &str, pressed: bool) {{
+        let key = InputBinding::Key(code.to_string());
         
-        loop {{
-            let now = Instant::now();
-            let dt = now.duration_since(last_frame).as_secs_f32();
-            last_frame = now;
+        ENGINE.with(|slot| {{
+            let mut slot = slot.borrow_mut();
+            let Some(input) = slot.as_mut().and_then(|engine| engine.world_mut().resource_mut::<Input>()) else {{
+                return;
+            }};
             
-            engine.update(dt);
-            
-            // Frame limiting
-            let elapsed = Instant::now().duration_since(now);
-            if elapsed < frame_time {{
-                std::thread::sleep(frame_time - elapsed);
+            if pressed {{
+                input.press(key);
+            }} else {{
+                input.release(&key);
             }}
-        }}
+        }});
     }}
     
-    #[cfg(target_arch = "wasm32")]
-    {{
-        // WASM game loop would be different
-        // Using requestAnimationFrame
+    // Steps the engine and hands back the frame's draw commands as JSON bytes
+    #[wasm_bindgen]
+    pub fn dream_update(dt: f32) -> Vec<u8> {{
+        ENGINE.with(|slot| {{
+            let mut slot = slot.borrow_mut();
+            let Some(engine) = slot.as_mut() else {{
+                return Vec::new();
+            }};
+            
+            engine.update(dt);
+            engine.get_render_frame().unwrap_or_default()
+        }})
     }}
-    
-    Ok(())
 }}
 "#);
         
@@ -197,23 +306,85 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
     }
     
     async fn generate_systems_code(&self, build_dir: &Path) -> Result<(), CompilerError> {
+        let (source, _) = self.systems_source_cached(Some(&build_dir.join("cache")))?;
+        fs::write(build_dir.join("src/systems.rs"), source)?;
+        Ok(())
+    }
+    
+    #[cfg(test)]
+    pub(crate) fn systems_source(&self) -> Result<String, CompilerError> {
+        self.systems_source_cached(None).map(|(source, _)| source)
+    }
+    
+    // With a cache directory, scripts already compiled there (keyed by a
+    // hash of the script) are reused and stale entries dropped. Also returns
+    // how many scripts had to be compiled
+    pub(crate) fn systems_source_cached(&self, cache_dir: Option<&Path>) -> Result<(String, usize), CompilerError> {
         let mut systems_code = String::new();
         let mut register_calls = Vec::new();
+        let mut cache_files = HashSet::new();
+        let mut system_names = HashSet::new();
+        let mut regenerated = 0;
         
         // Add imports
         systems_code.push_str("use dream_engine::*;\n\n");
         
+        // Named before filtering, so a system keeps its name across build types
+        let names: Vec<String> = self.project.scripts.iter()
+            .map(|script| unique_rust_name(&script.name, &mut system_names))
+            .collect();
+        let names_by_id: HashMap<&str, &str> = self.project.scripts.iter()
+            .zip(&names)
+            .map(|(script, name)| (script.id.as_str(), name.as_str()))
+            .collect();
+        
         // Compile each visual script
-        for script in &self.project.scripts {
-            let compiled = compile_visual_script(script)?;
-            systems_code.push_str(&compiled.code);
+        for (script, system_name) in self.project.scripts.iter().zip(&names) {
+            if script.debug_only && !self.includes_debug_systems() {
+                continue;
+            }
+            
+            let code = match cache_dir {
+                Some(dir) => {
+                    let file_name = format!("{}.rs", script_hash(script, system_name)?);
+                    let cached = dir.join(&file_name);
+                    cache_files.insert(file_name);
+                    
+                    match fs::read_to_string(&cached) {
+                        Ok(code) => code,
+                        Err(_) => {
+                            let code = compile_visual_script_as(script, system_name)?.code;
+                            fs::write(&cached, &code)?;
+                            regenerated += 1;
+                            code
+                        }
+                    }
+                }
+                None => {
+                    regenerated += 1;
+                    compile_visual_script_as(script, system_name)?.code
+                }
+            };
+            systems_code.push_str(&code);
             systems_code.push_str("\n\n");
             
-            let system_name = to_rust_name(&script.name);
-            register_calls.push(format!(
-                "    schedule.add_system(Box::new({}System {{}}))",
-                system_name
-            ));
+            // Systems are registered under their Rust name so scripts can order against each other
+            let mut after = Vec::new();
+            for id in &script.run_after {
+                let dependency = names_by_id.get(id.as_str()).ok_or_else(|| CompilerError::CodeGeneration(
+                    format!("Script {} runs after unknown script {}", script.name, id)
+                ))?;
+                after.push(format!("{:?}", dependency));
+            }
+            
+            register_calls.push(if after.is_empty() {
+                format!("    schedule.add_named_system(\"{0}\", Box::new({0}System {{}}))", system_name)
+            } else {
+                format!(
+                    "    schedule.add_system_after(\"{0}\", &[{1}], Box::new({0}System {{}}))",
+                    system_name, after.join(", ")
+                )
+            });
         }
         
         // Add register function
@@ -221,153 +392,67 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         systems_code.push_str(&register_calls.join(";\n"));
         systems_code.push_str(";\n}\n");
         
-        fs::write(build_dir.join("src/systems.rs"), systems_code)?;
-        Ok(())
-    }
-    
-    fn generate_entities_code(&self, build_dir: &Path) -> Result<(), CompilerError> {
-        let mut entities_code = String::new();
-        
-        entities_code.push_str("use dream_engine::*;\n\n");
-        entities_code.push_str("pub fn create_entities(world: &mut World, physics: &mut PhysicsWorld) {\n");
-        
-        // Generate entity creation code for each scene
-        for scene in &self.project.scenes {
-            entities_code.push_str(&format!("    // Scene: {}\n", scene.name));
-            
-            for object in &scene.objects {
-                entities_code.push_str(&format!(
-                    r#"    {{
-        let entity = world.create_entity();
-        
-        // Add transform
-        world.add_component(entity, Transform {{
-            position: Vec3::new({:.2}f32, {:.2}f32, 0.0),
-            rotation: Quat::from_rotation_z({:.2}f32),
-            scale: Vec3::new({:.2}f32, {:.2}f32, 1.0),
-        }});
-"#,
-                    object.position.x, object.position.y,
-                    object.rotation,
-                    object.scale.x, object.scale.y
-                ));
-                
-                // Add components based on object data
-                for component in &object.components {
-                    match component.component_type.as_str() {
-                        "Sprite" => {
-                            let texture_id = component.data.get("texture_id")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("default");
-                            
-                            entities_code.push_str(&format!(
-                                r#"        
-        // Add sprite
-        world.add_component(entity, Sprite {{
-            texture_id: "{}".to_string(),
-            color: [1.0, 1.0, 1.0, 1.0],
-            flip_x: false,
-            flip_y: false,
-            source_rect: None,
-            pivot: Vec2::new(0.5, 0.5),
-        }});
-"#,
-                                texture_id
-                            ));
-                        }
-                        
-                        "RigidBody" => {
-                            let body_type = component.data.get("body_type")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("Dynamic");
-                            let mass = component.data.get("mass")
-                                .and_then(|v| v.as_f64())
-                                .unwrap_or(1.0) as f32;
-                            
-                            entities_code.push_str(&format!(
-                                r#"        
-        // Add rigid body
-        let body = RigidBody::new(Vec2::new({:.2}f32, {:.2}f32), BodyType::{})
-            .with_mass({:.2}f32);
-        world.add_component(entity, body.clone());
-        physics.add_rigid_body(entity, body);
-"#,
-                                object.position.x, object.position.y,
-                                body_type,
-                                mass
-                            ));
-                        }
-                        
-                        "Collider" => {
-                            let collider_type = component.data.get("type")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("circle");
-                            
-                            match collider_type {
-                                "circle" => {
-                                    let radius = component.data.get("radius")
-                                        .and_then(|v| v.as_f64())
-                                        .unwrap_or(32.0) as f32;
-                                    
-                                    entities_code.push_str(&format!(
-                                        r#"        
-        // Add collider
-        let collider = Collider::circle({:.2}f32);
-        world.add_component(entity, collider.clone());
-        physics.add_collider(entity, collider);
-"#,
-                                        radius
-                                    ));
-                                }
-                                "box" => {
-                                    let width = component.data.get("width")
-                                        .and_then(|v| v.as_f64())
-                                        .unwrap_or(64.0) as f32;
-                                    let height = component.data.get("height")
-                                        .and_then(|v| v.as_f64())
-                                        .unwrap_or(64.0) as f32;
-                                    
-                                    entities_code.push_str(&format!(
-                                        r#"        
-        // Add collider
-        let collider = Collider::box_collider({:.2}f32, {:.2}f32);
-        world.add_component(entity, collider.clone());
-        physics.add_collider(entity, collider);
-"#,
-                                        width, height
-                                    ));
-                                }
-                                _ => {}
-                            }
-                        }
-                        
-                        _ => {
-                            // Custom components would be handled here
-                        }
-                    }
+        if let Some(dir) = cache_dir {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                if name.ends_with(".rs") && !cache_files.contains(name) {
+                    fs::remove_file(&path)?;
                 }
-                
-                entities_code.push_str("    }\n\n");
             }
         }
         
-        entities_code.push_str("}\n");
-        
-        fs::write(build_dir.join("src/entities.rs"), entities_code)?;
+        Ok((systems_code, regenerated))
+    }
+    
+    fn includes_debug_systems(&self) -> bool {
+        matches!(self.optimize_level, OptimizeLevel::Debug)
+    }
+    
+    fn generate_entities_code(&self, build_dir: &Path) -> Result<(), CompilerError> {
+        fs::write(build_dir.join("src/entities.rs"), self.entities_source()?)?;
         Ok(())
     }
     
-    async fn process_assets(&self, build_dir: &Path) -> Result<u64, CompilerError> {
+    // Embeds the same prefabs the editor preview instantiates, bincode-encoded
+    // so every float keeps its exact bits
+    pub(crate) fn entities_source(&self) -> Result<String, CompilerError> {
+        let prefabs: Vec<EntityData> = self.project.scenes.iter()
+            .flat_map(|scene| scene.objects.iter().map(GameObject::to_prefab))
+            .collect();
+        let bytes = bincode::serialize(&prefabs)
+            .map_err(|e| CompilerError::CodeGeneration(e.to_string()))?;
+        
+        Ok(format!(r#"use dream_engine::*;
+
+const ENTITIES: &[u8] = &{:?};
+
+pub fn create_entities(engine: &mut DreamEngine) -> Result<(), EngineError> {{
+    let prefabs: Vec<EntityData> = bincode::deserialize(ENTITIES)
+        .map_err(|e| EngineError::InvalidConfig(e.to_string()))?;
+    for prefab in &prefabs {{
+        engine.instantiate_prefab(prefab)?;
+    }}
+    Ok(())
+}}
+"#, bytes))
+    }
+    
+    // Returns the packed size and warnings about the assets
+    pub(crate) async fn process_assets(&self, build_dir: &Path) -> Result<(u64, Vec<String>), CompilerError> {
         let assets_dir = build_dir.join("assets");
         let mut total_size = 0u64;
+        let mut warnings = Vec::new();
         
         // Create asset manifest
         let mut manifest = AssetManifest {
-            textures: HashMap::new(),
-            audio: HashMap::new(),
-            data: HashMap::new(),
+            textures: BTreeMap::new(),
+            audio: BTreeMap::new(),
+            data: BTreeMap::new(),
         };
         
+        let mut pak = PakWriter::new();
+        
         // Process each asset
         for asset in &self.project.assets {
             let source_path = Path::new(&asset.path);
@@ -376,51 +461,55 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
                 continue;
             }
             
-            let file_size = fs::metadata(&source_path)?.len();
-            total_size += file_size;
+            let mut data = fs::read(source_path)?;
             
-            match asset.asset_type.as_str() {
+            // Textures are re-encoded smaller; audio goes in as-is and the
+            // game decodes both on load
+            let kind = match asset.asset_type.as_str() {
                 "texture" | "sprite" => {
-                    // Optimize textures (simplified - just copy for now)
-                    let dest_name = format!("{}.png", asset.id);
-                    let dest_path = assets_dir.join(&dest_name);
-                    fs::copy(&source_path, &dest_path)?;
+                    match optimize_texture(&data, self.max_texture_size) {
+                        Some(optimized) => {
+                            // Only worth a mention past 10%
+                            if optimized.len() * 10 < data.len() * 9 {
+                                warnings.push(format!(
+                                    "Texture {} optimized from {} to {} bytes",
+                                    asset.name, data.len(), optimized.len()
+                                ));
+                            }
+                            data = optimized;
+                        }
+                        None if image::guess_format(&data).is_err() => {
+                            warnings.push(format!("Texture {} is not a recognized image, packed as-is", asset.name));
+                        }
+                        None => {}
+                    }
                     
-                    manifest.textures.insert(asset.id.clone(), dest_name);
+                    manifest.textures.insert(asset.id.clone(), asset.name.clone());
+                    PakAssetKind::Texture
                 }
-                
                 "audio" => {
-                    // Compress audio (simplified - just copy for now)
-                    let dest_name = format!("{}.ogg", asset.id);
-                    let dest_path = assets_dir.join(&dest_name);
-                    fs::copy(&source_path, &dest_path)?;
-                    
-                    manifest.audio.insert(asset.id.clone(), dest_name);
+                    manifest.audio.insert(asset.id.clone(), asset.name.clone());
+                    PakAssetKind::Audio
                 }
-                
                 _ => {
-                    // Copy other assets as-is
-                    let dest_name = format!("{}.dat", asset.id);
-                    let dest_path = assets_dir.join(&dest_name);
-                    fs::copy(&source_path, &dest_path)?;
-                    
-                    manifest.data.insert(asset.id.clone(), dest_name);
+                    manifest.data.insert(asset.id.clone(), asset.name.clone());
+                    PakAssetKind::Data
                 }
-            }
+            };
+            total_size += data.len() as u64;
+            pak.add(asset.id.clone(), kind, data);
         }
         
-        // Create asset pack
+        // The manifest lists what's in the pack by id and display name
         let manifest_bytes = bincode::serialize(&manifest)?;
         fs::write(assets_dir.join("manifest.bin"), &manifest_bytes)?;
+        fs::write(assets_dir.join("assets.pak"), pak.finish())?;
         
-        // In production, you'd create a single PAK file
-        // For now, create a placeholder
-        fs::write(assets_dir.join("assets.pak"), b"PLACEHOLDER")?;
-        
-        Ok(total_size)
+        Ok((total_size, warnings))
     }
     
-    async fn build_executable(&self, build_dir: &Path) -> Result<PathBuf, CompilerError> {
+    // Returns the compiler warnings from the build
+    async fn build_executable(&self, build_dir: &Path) -> Result<Vec<String>, CompilerError> {
         let mut cmd = Command::new("cargo");
         cmd.current_dir(build_dir);
         
@@ -464,24 +553,59 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         }
         
         // Run the build
-        let output = cmd.output()?;
+        let warnings = run_cargo_build(cmd, &self.module_name())?;
+        
+        if matches!(self.target, BuildTarget::WebAssembly) {
+            self.generate_web_bindings(build_dir)?;
+        }
+        
+        Ok(warnings)
+    }
+    
+    // Runs the wasm-bindgen CLI over cargo's cdylib, leaving `<module>.js`
+    // and `<module>_bg.wasm` in `pkg/`
+    fn generate_web_bindings(&self, build_dir: &Path) -> Result<(), CompilerError> {
+        let profile = match self.optimize_level {
+            OptimizeLevel::Debug => "debug",
+            OptimizeLevel::Release => "release",
+            OptimizeLevel::ReleaseSmall => "release-small",
+        };
+        let module = self.module_name();
+        let wasm = build_dir.join(format!("target/wasm32-unknown-unknown/{}/{}.wasm", profile, module));
+        
+        let output = Command::new("wasm-bindgen")
+            .arg("--target")
+            .arg("web")
+            .arg("--no-typescript")
+            .arg("--out-dir")
+            .arg(build_dir.join("pkg"))
+            .arg("--out-name")
+            .arg(&module)
+            .arg(&wasm)
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => CompilerError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "wasm-bindgen not found; install it with `cargo install wasm-bindgen-cli`"
+                )),
+                _ => CompilerError::Io(e),
+            })?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(CompilerError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Build failed: {}", stderr)
-            )));
+            return Err(CompilerError::Io(std::io::Error::other(format!("wasm-bindgen failed: {}", stderr))));
         }
         
-        // Find the output executable
-        let exe_name = self.project.name.to_lowercase().replace(' ', "_");
-        let exe_path = match self.target {
+        Ok(())
+    }
+    
+    fn executable_path(&self, build_dir: &Path) -> PathBuf {
+        let exe_name = self.module_name();
+        match self.target {
             BuildTarget::Windows => build_dir.join(format!("target/release/{}.exe", exe_name)),
+            BuildTarget::WebAssembly => build_dir.join(format!("pkg/{}_bg.wasm", exe_name)),
             _ => build_dir.join(format!("target/release/{}", exe_name)),
-        };
-        
-        Ok(exe_path)
+        }
     }
     
     async fn package_game(
@@ -491,7 +615,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         asset_size: u64
     ) -> Result<BuildResult, CompilerError> {
         let output_dir = Path::new("target/games").join(&self.project.name);
-        fs::create_dir_all(&output_dir)?;
+        
+        if matches!(self.target, BuildTarget::WebAssembly) {
+            return self.package_web(build_dir, &build_dir.join("pkg"), &output_dir, asset_size);
+        }
+        
+        self.prepare_output_directory(&output_dir)?;
         
         // Copy executable
         let final_exe = output_dir.join(executable.file_name().unwrap());
@@ -520,6 +649,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
             fs::set_permissions(&final_exe, fs::Permissions::from_mode(0o755))?;
         }
         
+        self.normalize_package(&output_dir)?;
+        
         Ok(BuildResult {
             executable_path: final_exe.to_string_lossy().to_string(),
             assets_path: assets_output.to_string_lossy().to_string(),
@@ -527,28 +658,358 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
             warnings: vec![],
         })
     }
+    
+    // A browser-ready folder: index.html + the loop in game.js + the
+    // wasm-bindgen output from `bindings` + assets/. Serving the folder over
+    // HTTP is all that's needed to play
+    pub(crate) fn package_web(
+        &self,
+        build_dir: &Path,
+        bindings: &Path,
+        output_dir: &Path,
+        asset_size: u64
+    ) -> Result<BuildResult, CompilerError> {
+        self.prepare_output_directory(output_dir)?;
+        
+        let module = self.module_name();
+        let wasm_out = output_dir.join(format!("{}_bg.wasm", module));
+        fs::copy(bindings.join(format!("{}_bg.wasm", module)), &wasm_out)?;
+        
+        let bindings_out = output_dir.join(format!("{}.js", module));
+        fs::copy(bindings.join(format!("{}.js", module)), &bindings_out)?;
+        
+        let glue = web_glue_source(&module);
+        fs::write(output_dir.join("game.js"), &glue)?;
+        
+        let shell = web_shell_source(&self.project.name);
+        let shell_path = output_dir.join("index.html");
+        fs::write(&shell_path, &shell)?;
+        
+        let assets_output = output_dir.join("assets");
+        if build_dir.join("assets").exists() {
+            copy_dir_all(build_dir.join("assets"), &assets_output)?;
+        } else {
+            fs::create_dir_all(&assets_output)?;
+        }
+        
+        let code_size = fs::metadata(&wasm_out)?.len() + fs::metadata(&bindings_out)?.len();
+        
+        self.normalize_package(output_dir)?;
+        
+        Ok(BuildResult {
+            executable_path: shell_path.to_string_lossy().to_string(),
+            assets_path: assets_output.to_string_lossy().to_string(),
+            size_bytes: code_size + glue.len() as u64 + shell.len() as u64 + asset_size,
+            warnings: vec![],
+        })
+    }
+    
+    // Reproducible packages start from an empty folder so leftovers from an
+    // earlier build can't leak in
+    fn prepare_output_directory(&self, output_dir: &Path) -> Result<(), CompilerError> {
+        if self.reproducible_epoch.is_some() && output_dir.exists() {
+            fs::remove_dir_all(output_dir)?;
+        }
+        
+        fs::create_dir_all(output_dir)?;
+        Ok(())
+    }
+    
+    // Pins every mtime to the configured epoch and permissions to fixed modes,
+    // so copies and rebuilds don't differ in metadata
+    fn normalize_package(&self, output_dir: &Path) -> Result<(), CompilerError> {
+        let Some(epoch) = self.reproducible_epoch else {
+            return Ok(());
+        };
+        
+        let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(epoch);
+        normalize_tree(output_dir, mtime)?;
+        Ok(())
+    }
+    
+    fn module_name(&self) -> String {
+        self.project.name.to_lowercase().replace(' ', "_")
+    }
+}
+
+// ES module that loads the wasm-bindgen bindings and drives the game from
+// requestAnimationFrame
+pub(crate) fn web_glue_source(module: &str) -> String {
+    let loop_source = r#"
+export async function run(canvas) {
+    // Instantiating the module runs `dream_start`
+    await init();
+    const ctx = canvas.getContext('2d');
+    
+    window.addEventListener('keydown', (e) => {
+        if (!e.repeat) dream_key(e.code, true);
+    });
+    window.addEventListener('keyup', (e) => dream_key(e.code, false));
+    
+    let last = performance.now();
+    const frame = (now) => {
+        // Clamp so a backgrounded tab doesn't dump seconds into the accumulator
+        const dt = Math.min((now - last) / 1000, 0.25);
+        last = now;
+        
+        const bytes = dream_update(dt);
+        if (bytes.length > 0) {
+            const frame = parseFrame(bytes);
+            checkFrameSchema(frame);
+            draw(ctx, frame.commands);
+        }
+        
+        requestAnimationFrame(frame);
+    };
+    requestAnimationFrame(frame);
+}
+
+const decoder = new TextDecoder();
+
+// Games send whole JSON frames. Binary (DFRM/DFRD) and delta frames need a
+// decoder this glue doesn't have, so they stop the loop instead of misdrawing
+function parseFrame(bytes) {
+    const magic = decoder.decode(bytes.subarray(0, 4));
+    if (magic === 'DFRM' || magic === 'DFRD') {
+        throw new Error(`Binary ${magic} frames are not supported by the web glue`);
+    }
+    
+    const frame = JSON.parse(decoder.decode(bytes));
+    if (!Array.isArray(frame.commands)) {
+        throw new Error('Delta frames are not supported by the web glue');
+    }
+    return frame;
 }
 
+function rgba([r, g, b, a]) {
+    return `rgba(${r * 255}, ${g * 255}, ${b * 255}, ${a})`;
+}
+
+// A newer engine may emit commands this glue can't draw; those are skipped
+function checkFrameSchema(frame) {
+    if (frame.version > FRAME_SCHEMA_VERSION && !warnedKinds.has('@version')) {
+        warnedKinds.add('@version');
+        console.warn(`Engine frame schema v${frame.version} is newer than supported v${FRAME_SCHEMA_VERSION}`);
+    }
+    
+    for (const kind of frame.kinds ?? []) {
+        if (!SUPPORTED_KINDS.includes(kind) && !warnedKinds.has(kind)) {
+            warnedKinds.add(kind);
+            console.warn(`Skipping unsupported draw command: ${kind}`);
+        }
+    }
+}
+
+function draw(ctx, commands) {
+    for (const command of commands) {
+        const c = command.data;
+        switch (command.type) {
+            case 'Clear':
+                ctx.fillStyle = rgba(c.color);
+                ctx.fillRect(0, 0, ctx.canvas.width, ctx.canvas.height);
+                break;
+            case 'DrawSprite': {
+                // Textures come with the asset pack; until then sprites draw as
+                // tinted quads, sized by their atlas frame when they have one
+                const w = (c.src ? c.src.width : 32) * c.scale.x;
+                const h = (c.src ? c.src.height : 32) * c.scale.y;
+                ctx.save();
+                ctx.translate(c.position.x, c.position.y);
+                ctx.rotate(c.rotation);
+                ctx.scale(c.flip_x ? -1 : 1, c.flip_y ? -1 : 1);
+                ctx.fillStyle = rgba(c.color);
+                ctx.fillRect(-c.pivot.x * w, -c.pivot.y * h, w, h);
+                ctx.restore();
+                break;
+            }
+            case 'DrawRect':
+                ctx.fillStyle = rgba(c.color);
+                ctx.fillRect(c.position.x, c.position.y, c.size.x, c.size.y);
+                break;
+            case 'DrawLine':
+                ctx.strokeStyle = rgba(c.color);
+                ctx.lineWidth = c.width;
+                ctx.beginPath();
+                ctx.moveTo(c.start.x, c.start.y);
+                ctx.lineTo(c.end.x, c.end.y);
+                ctx.stroke();
+                break;
+            case 'DrawCircle':
+                ctx.fillStyle = rgba(c.color);
+                ctx.beginPath();
+                ctx.arc(c.center.x, c.center.y, c.radius, 0, Math.PI * 2);
+                ctx.fill();
+                break;
+            case 'DrawText':
+                // Bitmap fonts aren't loaded here, so text uses a system font
+                ctx.font = `${c.size}px monospace`;
+                ctx.fillStyle = rgba(c.color);
+                ctx.fillText(c.text, c.position.x, c.position.y);
+                break;
+            case 'DrawTilemap':
+                // Same placeholder as sprites: one quad per tile, centered on it
+                ctx.fillStyle = 'rgba(255, 255, 255, 0.5)';
+                for (const tile of c.tiles) {
+                    ctx.fillRect(tile.position.x - tile.src.width / 2, tile.position.y - tile.src.height / 2, tile.src.width, tile.src.height);
+                }
+                break;
+        }
+    }
+}
+"#;
+    
+    format!(
+        "// Generated by Dream Emulator\nimport init, {{ dream_key, dream_update }} from './{}.js';\n\n\
+         // The frame schema of the engine this game was built with\n\
+         const FRAME_SCHEMA_VERSION = {};\n\
+         const SUPPORTED_KINDS = {:?};\n\
+         const warnedKinds = new Set();\n{}",
+        module, FRAME_SCHEMA_VERSION, FRAME_COMMAND_KINDS, loop_source
+    )
+}
+
+fn web_shell_source(title: &str) -> String {
+    format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <title>{}</title>
+    <style>
+        html, body {{ margin: 0; height: 100%; background: #000; overflow: hidden; }}
+        canvas {{ display: block; width: 100vw; height: 100vh; }}
+    </style>
+</head>
+<body>
+    <canvas id="game" tabindex="0"></canvas>
+    <script type="module">
+        import {{ run }} from './game.js';
+        
+        const canvas = document.getElementById('game');
+        const resize = () => {{
+            canvas.width = window.innerWidth;
+            canvas.height = window.innerHeight;
+        }};
+        window.addEventListener('resize', resize);
+        resize();
+        canvas.focus();
+        
+        run(canvas);
+    </script>
+</body>
+</html>
+"#,
+        html_escape(title)
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Re-encodes an image as a maximally compressed PNG, scaled down to fit
+// `max_size` if given. Metadata chunks aren't carried over. None when the
+// data isn't a decodable image or the result wouldn't be any smaller
+pub(crate) fn optimize_texture(data: &[u8], max_size: Option<u32>) -> Option<Vec<u8>> {
+    use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+    use image::{GenericImageView, ImageEncoder};
+    
+    let mut img = image::load_from_memory(data).ok()?;
+    let (width, height) = img.dimensions();
+    let downscale = max_size.filter(|&max| width > max || height > max);
+    if let Some(max) = downscale {
+        img = img.resize(max, max, image::imageops::FilterType::Lanczos3);
+    }
+    
+    let mut out = Vec::new();
+    PngEncoder::new_with_quality(&mut out, CompressionType::Best, FilterType::Adaptive)
+        .write_image(img.as_bytes(), img.width(), img.height(), img.color())
+        .ok()?;
+    
+    // A smaller image is kept even if the encoding happened to grow
+    (downscale.is_some() || out.len() < data.len()).then_some(out)
+}
+
+// Runs `cmd` (a cargo build) with JSON diagnostics. Succeeds with the
+// rendered warnings from `package` (the engine and other path dependencies
+// report theirs too), or fails with every rendered error
+pub(crate) fn run_cargo_build(mut cmd: Command, package: &str) -> Result<Vec<String>, CompilerError> {
+    let output = cmd.arg("--message-format=json").output()?;
+    
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-message" {
+            continue;
+        }
+        
+        let diagnostic = &message["message"];
+        // Summaries like "1 warning emitted" point at no code
+        if diagnostic["spans"].as_array().filter(|spans| !spans.is_empty()).is_none() {
+            continue;
+        }
+        
+        let text = diagnostic["rendered"].as_str()
+            .or_else(|| diagnostic["message"].as_str())
+            .unwrap_or_default()
+            .trim_end()
+            .to_string();
+        
+        match diagnostic["level"].as_str() {
+            Some("warning") if message["package_id"].as_str().is_some_and(|id| is_package(id, package)) => warnings.push(text),
+            Some("error") => errors.push(text),
+            _ => {}
+        }
+    }
+    
+    if !output.status.success() {
+        // Failures outside rustc (bad manifest, missing target) only reach stderr
+        if errors.is_empty() {
+            errors.push(String::from_utf8_lossy(&output.stderr).trim_end().to_string());
+        }
+        return Err(CompilerError::BuildFailed(errors.join("\n\n")));
+    }
+    
+    Ok(warnings)
+}
+
+// Cargo writes package ids as `name 0.1.0 (path+file:///dir)` before 1.77,
+// and as `path+file:///dir#name@0.1.0` (or `dir#0.1.0` when the directory
+// is named after the package) since
+fn is_package(package_id: &str, name: &str) -> bool {
+    match package_id.rsplit_once('#') {
+        Some((url, fragment)) => match fragment.split_once('@') {
+            Some((package, _)) => package == name,
+            None => url.rsplit('/').next() == Some(name),
+        },
+        None => package_id.split(' ').next() == Some(name),
+    }
+}
+
+// Ordered maps so the serialized manifest doesn't depend on hash seeds
 #[derive(Serialize, Deserialize)]
 struct AssetManifest {
-    textures: HashMap<String, String>,
-    audio: HashMap<String, String>,
-    data: HashMap<String, String>,
+    textures: BTreeMap<String, String>,
+    audio: BTreeMap<String, String>,
+    data: BTreeMap<String, String>,
 }
 
-fn to_rust_name(name: &str) -> String {
-    name.chars()
-        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '_')
-        .collect::<String>()
-        .split_whitespace()
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().chain(chars).collect(),
-            }
-        })
-        .collect()
+// Includes the engine version, since codegen changes between releases
+fn script_hash(script: &VisualScript, system_name: &str) -> Result<String, CompilerError> {
+    let json = serde_json::to_vec(script)
+        .map_err(|e| CompilerError::CodeGeneration(e.to_string()))?;
+    
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(system_name.as_bytes());
+    hasher.update(&json);
+    Ok(hasher.finalize().to_hex().to_string())
 }
 
 fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
@@ -563,4 +1024,56 @@ fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+// Children first, so setting a directory's mtime isn't undone by writes inside it
+fn normalize_tree(path: &Path, mtime: std::time::SystemTime) -> std::io::Result<()> {
+    let metadata = fs::metadata(path)?;
+    
+    if metadata.is_dir() {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+        
+        for entry in entries {
+            normalize_tree(&entry, mtime)?;
+        }
+    }
+    
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let executable = metadata.is_dir() || metadata.permissions().mode() & 0o111 != 0;
+        let mode = if executable { 0o755 } else { 0o644 };
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    
+    set_modified(path, metadata.is_dir(), mtime)
+}
+
+// Windows only sets times through a handle allowed to write attributes, and
+// needs backup semantics to open a directory at all
+#[cfg(windows)]
+fn set_modified(path: &Path, _is_dir: bool, mtime: std::time::SystemTime) -> std::io::Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+    const FILE_WRITE_ATTRIBUTES: u32 = 0x0100;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    
+    fs::OpenOptions::new()
+        .access_mode(FILE_WRITE_ATTRIBUTES)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)?
+        .set_modified(mtime)
+}
+
+// Directories can't be opened for writing, but a read handle is enough for them
+#[cfg(not(windows))]
+fn set_modified(path: &Path, is_dir: bool, mtime: std::time::SystemTime) -> std::io::Result<()> {
+    let file = if is_dir {
+        fs::File::open(path)?
+    } else {
+        fs::OpenOptions::new().write(true).open(path)?
+    };
+    file.set_modified(mtime)
+}
@@ -1,5 +1,8 @@
 // src-tauri/engine/src/compiler/mod.rs
-use crate::{VisualScript, VisualScriptNode, Project};
+mod builder;
+mod pak;
+
+use crate::{VisualScript, VisualScriptNode, VisualScriptConnection};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -12,29 +15,141 @@ pub struct CompiledSystem {
 
 #[derive(Debug, thiserror::Error)]
 pub enum CompilerError {
-    #[error("Unknown node type: {0}")]
-    UnknownNode(String),
+    #[error("Unknown node type {node_type} (node {node_id} at {position:?})")]
+    UnknownNode { node_type: String, node_id: String, position: (f32, f32) },
     
     #[error("Invalid connection: {0}")]
     InvalidConnection(String),
     
+    #[error("Connections form a cycle: {0}")]
+    Cycle(String),
+    
+    #[error("Type mismatch at node {node} ({position:?}): expected {expected}, found {found}")]
+    TypeMismatch { node: String, position: (f32, f32), expected: String, found: String },
+    
     #[error("Code generation failed: {0}")]
     CodeGeneration(String),
     
+    #[error("Build failed:\n{0}")]
+    BuildFailed(String),
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+impl CompilerError {
+    // The node the editor should highlight, with its canvas position
+    pub fn node(&self) -> Option<(&str, (f32, f32))> {
+        match self {
+            CompilerError::UnknownNode { node_id, position, .. } => Some((node_id, *position)),
+            CompilerError::TypeMismatch { node, position, .. } => Some((node, *position)),
+            _ => None,
+        }
+    }
+}
+
+// Types the compiler can track through node outputs; anything else is
+// passed through unchecked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueType {
+    Float,
+    Bool,
+    Vec2,
+    Vec3,
+    Entity,
+}
+
+impl ValueType {
+    fn is_vector(self) -> bool {
+        matches!(self, ValueType::Vec2 | ValueType::Vec3)
+    }
+    
+    fn rust_name(self) -> &'static str {
+        match self {
+            ValueType::Float => "f32",
+            ValueType::Bool => "bool",
+            ValueType::Vec2 => "Vec2",
+            ValueType::Vec3 => "Vec3",
+            ValueType::Entity => "Entity",
+        }
+    }
+    
+    // Fields scripts can read and write directly
+    fn of_field(component: &str, field: &str) -> Option<Self> {
+        match (component, field) {
+            ("Transform", "position" | "scale") => Some(ValueType::Vec3),
+            ("RigidBody", "position" | "velocity" | "force") => Some(ValueType::Vec2),
+            ("RigidBody", "rotation" | "angular_velocity" | "torque" | "mass") => Some(ValueType::Float),
+            ("Sprite", "pivot") => Some(ValueType::Vec2),
+            ("Sprite", "flip_x" | "flip_y") => Some(ValueType::Bool),
+            _ => None,
+        }
+    }
+}
+
 pub fn compile_visual_script(script: &VisualScript) -> Result<CompiledSystem, CompilerError> {
+    compile_visual_script_as(script, &to_rust_name(&script.name))
+}
+
+// For callers that have already made `system_name` unique among siblings
+pub(crate) fn compile_visual_script_as(script: &VisualScript, system_name: &str) -> Result<CompiledSystem, CompilerError> {
     let mut compiler = ScriptCompiler::new();
-    compiler.compile(script)
+    compiler.compile(script, system_name)
+}
+
+// PascalCase identifier for a user-facing name. Always valid Rust: names
+// that would be empty or start with a digit get a leading underscore. Every
+// keyword but `Self` is lowercase, so capitalising already steers clear of
+// them; `Self` gets a trailing underscore
+pub(crate) fn to_rust_name(name: &str) -> String {
+    let mut ident: String = name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ' || *c == '_')
+        .collect::<String>()
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+            }
+        })
+        .collect();
+    
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if ident == "Self" {
+        ident.push('_');
+    }
+    
+    ident
+}
+
+// `to_rust_name`, numbered when an earlier name already took it
+pub(crate) fn unique_rust_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let base = to_rust_name(name);
+    let mut ident = base.clone();
+    let mut n = 2;
+    
+    while !taken.insert(ident.clone()) {
+        ident = format!("{}{}", base, n);
+        n += 1;
+    }
+    
+    ident
 }
 
 struct ScriptCompiler {
     code: Vec<String>,
     indent_level: usize,
     temp_vars: HashMap<String, String>,
+    // Known types of the expressions in `temp_vars`
+    value_types: HashMap<String, ValueType>,
     var_counter: usize,
+    // Nodes in emission order, and which have been emitted into some block
+    order: Vec<VisualScriptNode>,
+    connections: Vec<VisualScriptConnection>,
+    emitted: HashSet<String>,
 }
 
 impl ScriptCompiler {
@@ -43,18 +158,22 @@ impl ScriptCompiler {
             code: Vec::new(),
             indent_level: 0,
             temp_vars: HashMap::new(),
+            value_types: HashMap::from([("dt".to_string(), ValueType::Float)]),
             var_counter: 0,
+            order: Vec::new(),
+            connections: Vec::new(),
+            emitted: HashSet::new(),
         }
     }
     
-    fn compile(&mut self, script: &VisualScript) -> Result<CompiledSystem, CompilerError> {
+    fn compile(&mut self, script: &VisualScript, system_name: &str) -> Result<CompiledSystem, CompilerError> {
         // Generate imports
-        self.write_line("use dream_engine::{World, PhysicsWorld, System, EntityId};");
+        self.write_line("use dream_engine::{World, PhysicsWorld, System, Commands, EntityId};");
         self.write_line("use dream_engine::{Transform, Sprite, RigidBody, Vec2, Vec3};");
+        self.write_line("use dream_engine::{Input, InputBinding};");
         self.write_line("");
         
         // Generate system struct
-        let system_name = self.to_rust_name(&script.name);
         self.write_line(&format!("pub struct {}System {{", system_name));
         self.indent();
         self.write_line("// System state");
@@ -66,16 +185,17 @@ impl ScriptCompiler {
         self.write_line(&format!("impl System for {}System {{", system_name));
         self.indent();
         
-        self.write_line("fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, dt: f32) {");
+        self.write_line("fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, commands: &mut Commands, dt: f32) {");
         self.indent();
         
         // Sort nodes topologically
         let sorted_nodes = self.topological_sort(&script.nodes, &script.connections)?;
+        self.order = sorted_nodes.into_iter().cloned().collect();
+        self.connections = script.connections.clone();
         
-        // Compile each node
-        for node in sorted_nodes {
-            self.compile_node(node)?;
-        }
+        // Compile each node; control-flow nodes pull their branches in as they go
+        let all: HashSet<String> = self.order.iter().map(|n| n.id.clone()).collect();
+        self.compile_scope(&all)?;
         
         self.dedent();
         self.write_line("}");
@@ -89,6 +209,90 @@ impl ScriptCompiler {
         })
     }
     
+    // Emits the not-yet-emitted nodes of `scope` in order, then closes any
+    // blocks they left open so the scope ends balanced
+    fn compile_scope(&mut self, scope: &HashSet<String>) -> Result<(), CompilerError> {
+        let base_indent = self.indent_level;
+        let nodes: Vec<VisualScriptNode> = self.order.iter()
+            .filter(|n| scope.contains(&n.id))
+            .cloned()
+            .collect();
+        
+        for node in &nodes {
+            if self.emitted.insert(node.id.clone()) {
+                self.wire_inputs(&node.id);
+                self.compile_node(node)?;
+            }
+        }
+        
+        while self.indent_level > base_indent {
+            self.dedent();
+            self.write_line("}");
+        }
+        
+        Ok(())
+    }
+    
+    // Points each input of `node_id` at the variable its connected output
+    // was compiled to: `<source>_<handle>` if the source names its outputs,
+    // otherwise the source's single output
+    fn wire_inputs(&mut self, node_id: &str) {
+        for conn in self.connections.iter().filter(|c| c.target == node_id) {
+            let output = self.temp_vars.get(&format!("{}_{}", conn.source, conn.source_handle))
+                .or_else(|| self.temp_vars.get(&conn.source))
+                .cloned();
+            
+            if let Some(var) = output {
+                self.temp_vars.insert(format!("{}_{}", conn.target, conn.target_handle), var);
+            }
+        }
+    }
+    
+    // Unemitted nodes reachable from `node_id` through outputs accepted by
+    // `handle`, following every connection after the first hop
+    fn downstream(&self, node_id: &str, handle: impl Fn(&str) -> bool) -> HashSet<String> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<&str> = self.connections.iter()
+            .filter(|c| c.source == node_id && handle(&c.source_handle))
+            .map(|c| c.target.as_str())
+            .collect();
+        
+        while let Some(id) = stack.pop() {
+            if self.emitted.contains(id) || !reached.insert(id.to_string()) {
+                continue;
+            }
+            stack.extend(self.connections.iter()
+                .filter(|c| c.source == id)
+                .map(|c| c.target.as_str()));
+        }
+        
+        reached
+    }
+    
+    // Unemitted nodes outside `scope` that feed into it
+    fn upstream(&self, scope: &HashSet<String>) -> HashSet<String> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<&str> = scope.iter().map(String::as_str).collect();
+        
+        while let Some(id) = stack.pop() {
+            for conn in self.connections.iter().filter(|c| c.target == id) {
+                let source = conn.source.as_str();
+                if !self.emitted.contains(source) && !scope.contains(source) && reached.insert(source.to_string()) {
+                    stack.push(source);
+                }
+            }
+        }
+        
+        reached
+    }
+    
+    // Values a block's body reads from outside it are computed before the
+    // block opens
+    fn compile_prerequisites(&mut self, body: &HashSet<String>) -> Result<(), CompilerError> {
+        let prerequisites = self.upstream(body);
+        self.compile_scope(&prerequisites)
+    }
+    
     fn compile_node(&mut self, node: &VisualScriptNode) -> Result<(), CompilerError> {
         self.write_line(&format!("// {}", node.data.get("label")
             .and_then(|v| v.as_str())
@@ -103,8 +307,10 @@ impl ScriptCompiler {
             "event/collision" => {
                 self.write_line("for event in physics.get_collision_events() {");
                 self.indent();
-                self.temp_vars.insert(format!("{}_entity_a", node.id), "event.entity_a".to_string());
-                self.temp_vars.insert(format!("{}_entity_b", node.id), "event.entity_b".to_string());
+                // Physics reports raw ids; upgrade them to live handles
+                self.write_line("let (Some(entity_a), Some(entity_b)) = (world.entity(event.entity_a), world.entity(event.entity_b)) else { continue };");
+                self.temp_vars.insert(format!("{}_entity_a", node.id), "entity_a".to_string());
+                self.temp_vars.insert(format!("{}_entity_b", node.id), "entity_b".to_string());
                 self.temp_vars.insert(format!("{}_contact", node.id), "event.contact".to_string());
             }
             
@@ -123,11 +329,12 @@ impl ScriptCompiler {
                     .join(", ");
                 
                 self.write_line(&format!(
-                    "for (entity, ({})) in world.query::<({})>().iter() {{",
+                    "for (entity_id, ({})) in world.query::<({})>().iter() {{",
                     components.join(", "),
                     component_refs
                 ));
                 self.indent();
+                self.write_line("let Some(entity) = world.entity(entity_id) else { continue };");
                 
                 self.temp_vars.insert(format!("{}_entities", node.id), "entity".to_string());
             }
@@ -176,28 +383,196 @@ impl ScriptCompiler {
             "math/add" => {
                 let a = self.get_input(&node.id, "a").unwrap_or_else(|| "0.0".to_string());
                 let b = self.get_input(&node.id, "b").unwrap_or_else(|| "0.0".to_string());
+                let output_type = self.same_type(&node.id, &a, &b)?;
                 let output_var = self.gen_var("sum");
                 
                 self.write_line(&format!("let {} = {} + {};", output_var, a, b));
-                self.temp_vars.insert(node.id.clone(), output_var);
+                self.set_output(&node.id, output_var, output_type);
             }
             
             "math/multiply" => {
                 let a = self.get_input(&node.id, "a").unwrap_or_else(|| "1.0".to_string());
                 let b = self.get_input(&node.id, "b").unwrap_or_else(|| "1.0".to_string());
+                
+                // Vectors only scale by a scalar on the right
+                self.expect_type(&node.id, &b, ValueType::Float)?;
+                let output_type = self.type_of(&a);
                 let output_var = self.gen_var("product");
                 
                 self.write_line(&format!("let {} = {} * {};", output_var, a, b));
-                self.temp_vars.insert(node.id.clone(), output_var);
+                self.set_output(&node.id, output_var, output_type);
             }
             
-            "flow/if" => {
+            "math/vector_scale" => {
+                let vector = self.get_input(&node.id, "vector").unwrap_or_else(|| "Vec3::ZERO".to_string());
+                let scalar = self.get_input(&node.id, "scalar").unwrap_or_else(|| "1.0".to_string());
+                
+                let vector_type = self.type_of(&vector).unwrap_or(ValueType::Vec3);
+                if !vector_type.is_vector() {
+                    return Err(self.mismatch(&node.id, "Vec2 or Vec3", vector_type));
+                }
+                self.expect_type(&node.id, &scalar, ValueType::Float)?;
+                
+                let output_var = self.gen_var("scaled");
+                self.write_line(&format!("let {} = {} * {};", output_var, vector, scalar));
+                self.set_output(&node.id, output_var, Some(vector_type));
+            }
+            
+            "math/constant" => {
+                let value = node.data.get("value").cloned().unwrap_or(serde_json::Value::Null);
+                let component = |axis: &str| value.get(axis).and_then(|v| v.as_f64()).map(|v| v as f32);
+                
+                let (literal, value_type) = match (value.as_f64(), component("x"), component("y"), component("z")) {
+                    (Some(v), ..) => (format!("{:?}", v as f32), ValueType::Float),
+                    (None, Some(x), Some(y), Some(z)) => (format!("Vec3::new({:?}, {:?}, {:?})", x, y, z), ValueType::Vec3),
+                    (None, Some(x), Some(y), None) => (format!("Vec2::new({:?}, {:?})", x, y), ValueType::Vec2),
+                    _ => return Err(CompilerError::CodeGeneration(format!("Constant {} has no numeric value", node.id))),
+                };
+                
+                self.set_output(&node.id, literal, Some(value_type));
+            }
+            
+            "component/get_field" => {
+                let entity_var = self.get_input(&node.id, "entity")
+                    .unwrap_or_else(|| "entity".to_string());
+                let (component_type, field) = Self::field_target(node)?;
+                
+                let output_var = self.gen_var("field");
+                self.write_line(&format!(
+                    "let {} = world.get_component::<{}>({}).map(|c| c.{}).unwrap_or_default();",
+                    output_var, component_type, entity_var, field
+                ));
+                self.set_output(&node.id, output_var, ValueType::of_field(component_type, field));
+            }
+            
+            "component/set_field" => {
+                let entity_var = self.get_input(&node.id, "entity")
+                    .unwrap_or_else(|| "entity".to_string());
+                let value = self.get_input(&node.id, "value")
+                    .ok_or_else(|| CompilerError::InvalidConnection("Missing value input".to_string()))?;
+                let (component_type, field) = Self::field_target(node)?;
+                
+                if let Some(field_type) = ValueType::of_field(component_type, field) {
+                    self.expect_type(&node.id, &value, field_type)?;
+                }
+                
+                self.write_line(&format!(
+                    "if let Some(component) = world.get_component_mut::<{}>({}) {{ component.{} = {}; }}",
+                    component_type, entity_var, field, value
+                ));
+            }
+            
+            "flow/if" | "flow/branch" => {
                 let condition = self.get_input(&node.id, "condition")
                     .unwrap_or_else(|| "false".to_string());
+                self.expect_type(&node.id, &condition, ValueType::Bool)?;
+                
+                // Nodes both branches lead to run after the if, not in it
+                let mut then_scope = self.downstream(&node.id, |h| h == "then");
+                let mut else_scope = self.downstream(&node.id, |h| h == "else");
+                let shared: HashSet<String> = then_scope.intersection(&else_scope).cloned().collect();
+                then_scope.retain(|id| !shared.contains(id));
+                else_scope.retain(|id| !shared.contains(id));
+                self.compile_prerequisites(&then_scope.union(&else_scope).cloned().collect())?;
                 
                 self.write_line(&format!("if {} {{", condition));
                 self.indent();
-                // The then/else branches would be handled by connected nodes
+                self.compile_scope(&then_scope)?;
+                self.dedent();
+                
+                if !else_scope.is_empty() {
+                    self.write_line("} else {");
+                    self.indent();
+                    self.compile_scope(&else_scope)?;
+                    self.dedent();
+                }
+                
+                self.write_line("}");
+            }
+            
+            "flow/for_each_entity" => {
+                let components = node.data.get("components")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec!["Transform"]);
+                let query = match components.as_slice() {
+                    [component] => format!("&{}", component),
+                    _ => format!("({})", components.iter()
+                        .map(|c| format!("&{}", c))
+                        .collect::<Vec<_>>()
+                        .join(", ")),
+                };
+                
+                let entity_var = self.gen_var("entity");
+                self.temp_vars.insert(format!("{}_entity", node.id), entity_var.clone());
+                self.value_types.insert(entity_var.clone(), ValueType::Entity);
+                
+                // Everything fed by the loop runs once per entity
+                let body = self.downstream(&node.id, |h| h != "completed");
+                self.compile_prerequisites(&body)?;
+                
+                // Ids are collected first so the body can borrow the world mutably
+                self.write_line(&format!("let ids: Vec<EntityId> = world.query::<{}>().map(|(id, _)| id).collect();", query));
+                self.write_line("for entity_id in ids {");
+                self.indent();
+                self.write_line(&format!("let Some({}) = world.entity(entity_id) else {{ continue }};", entity_var));
+                self.compile_scope(&body)?;
+                self.dedent();
+                self.write_line("}");
+            }
+            
+            "logic/compare" => {
+                let a = self.get_input(&node.id, "a").unwrap_or_else(|| "0.0".to_string());
+                let b = self.get_input(&node.id, "b").unwrap_or_else(|| "0.0".to_string());
+                let op = node.data.get("operator")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("==");
+                
+                if !matches!(op, "==" | "!=" | "<" | "<=" | ">" | ">=") {
+                    return Err(CompilerError::CodeGeneration(format!("Unknown comparison operator: {}", op)));
+                }
+                
+                self.same_type(&node.id, &a, &b)?;
+                let output_var = self.gen_var("compare");
+                self.write_line(&format!("let {} = {} {} {};", output_var, a, op, b));
+                self.set_output(&node.id, output_var, Some(ValueType::Bool));
+            }
+            
+            "input/key_down" => {
+                let key = node.data.get("key")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| CompilerError::CodeGeneration(format!("Key node {} has no key", node.id)))?;
+                // "pressed" fires once per press; the default holds while the key is down
+                let check = match node.data.get("mode").and_then(|v| v.as_str()) {
+                    Some("pressed") => "is_just_pressed",
+                    _ => "is_pressed",
+                };
+                
+                let output_var = self.gen_var("key_down");
+                self.write_line(&format!(
+                    "let {} = world.resource::<Input>().map_or(false, |input| input.{}(&InputBinding::key({:?})));",
+                    output_var, check, key
+                ));
+                self.set_output(&node.id, output_var, Some(ValueType::Bool));
+            }
+            
+            "input/mouse_position" => {
+                let output_var = self.gen_var("mouse");
+                self.write_line(&format!(
+                    "let {} = world.resource::<Input>().map_or(Vec2::ZERO, |input| input.mouse_position());",
+                    output_var
+                ));
+                self.set_output(&node.id, output_var, Some(ValueType::Vec2));
+            }
+            
+            "logic/bool" => {
+                let value = node.data.get("value")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                
+                self.set_output(&node.id, value.to_string(), Some(ValueType::Bool));
             }
             
             "flow/foreach" => {
@@ -218,9 +593,9 @@ impl ScriptCompiler {
                     .and_then(|v| v.as_str())
                     .unwrap_or("default");
                 
-                self.write_line("let new_entity = world.create_entity();");
+                // Deferred so spawning inside a query loop is safe
                 self.write_line(&format!(
-                    "world.add_component(new_entity, Transform::from_position({}));",
+                    "commands.spawn().with(Transform::from_position({}));",
                     position
                 ));
                 
@@ -232,11 +607,15 @@ impl ScriptCompiler {
                 let entity = self.get_input(&node.id, "entity")
                     .unwrap_or_else(|| "entity".to_string());
                 
-                self.write_line(&format!("world.destroy_entity({});", entity));
+                self.write_line(&format!("commands.despawn({});", entity));
             }
             
             _ => {
-                return Err(CompilerError::UnknownNode(node.get_type().to_string()));
+                return Err(CompilerError::UnknownNode {
+                    node_type: node.get_type().to_string(),
+                    node_id: node.id.clone(),
+                    position: node.position,
+                });
             }
         }
         
@@ -250,7 +629,8 @@ impl ScriptCompiler {
     ) -> Result<Vec<&VisualScriptNode>, CompilerError> {
         let mut sorted = Vec::new();
         let mut visited = HashSet::new();
-        let mut temp_visited = HashSet::new();
+        // The current DFS path, so a cycle can be reported node by node
+        let mut path = Vec::new();
         
         let node_map: HashMap<_, _> = nodes.iter()
             .map(|n| (n.id.as_str(), n))
@@ -273,23 +653,25 @@ impl ScriptCompiler {
             graph: &HashMap<&str, Vec<&str>>,
             node_map: &HashMap<&str, &'a VisualScriptNode>,
             visited: &mut HashSet<String>,
-            temp_visited: &mut HashSet<String>,
+            path: &mut Vec<String>,
             sorted: &mut Vec<&'a VisualScriptNode>
         ) -> Result<(), CompilerError> {
-            if temp_visited.contains(node_id) {
-                return Err(CompilerError::InvalidConnection("Cycle detected".to_string()));
+            if let Some(start) = path.iter().position(|id| id == node_id) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(node_id.to_string());
+                return Err(CompilerError::Cycle(cycle.join(" -> ")));
             }
             
             if !visited.contains(node_id) {
-                temp_visited.insert(node_id.to_string());
+                path.push(node_id.to_string());
                 
                 if let Some(neighbors) = graph.get(node_id) {
                     for &neighbor in neighbors {
-                        visit(neighbor, graph, node_map, visited, temp_visited, sorted)?;
+                        visit(neighbor, graph, node_map, visited, path, sorted)?;
                     }
                 }
                 
-                temp_visited.remove(node_id);
+                path.pop();
                 visited.insert(node_id.to_string());
                 
                 if let Some(node) = node_map.get(node_id) {
@@ -302,7 +684,7 @@ impl ScriptCompiler {
         
         for node in nodes {
             if !visited.contains(&node.id) {
-                visit(&node.id, &graph, &node_map, &mut visited, &mut temp_visited, &mut sorted)?;
+                visit(&node.id, &graph, &node_map, &mut visited, &mut path, &mut sorted)?;
             }
         }
         
@@ -310,6 +692,67 @@ impl ScriptCompiler {
         Ok(sorted)
     }
     
+    fn set_output(&mut self, node_id: &str, expr: String, value_type: Option<ValueType>) {
+        if let Some(value_type) = value_type {
+            self.value_types.insert(expr.clone(), value_type);
+        }
+        self.temp_vars.insert(node_id.to_string(), expr);
+    }
+    
+    // Literals are typed by their spelling; variables by the node that made them
+    fn type_of(&self, expr: &str) -> Option<ValueType> {
+        if let Some(value_type) = self.value_types.get(expr) {
+            return Some(*value_type);
+        }
+        
+        match expr {
+            "true" | "false" => Some(ValueType::Bool),
+            _ if expr.starts_with("Vec2::") => Some(ValueType::Vec2),
+            _ if expr.starts_with("Vec3::") => Some(ValueType::Vec3),
+            _ if expr.parse::<f32>().is_ok() => Some(ValueType::Float),
+            _ => None,
+        }
+    }
+    
+    fn expect_type(&self, node_id: &str, expr: &str, expected: ValueType) -> Result<(), CompilerError> {
+        match self.type_of(expr) {
+            Some(found) if found != expected => Err(self.mismatch(node_id, expected.rust_name(), found)),
+            _ => Ok(()),
+        }
+    }
+    
+    // Both operands must agree where both are known; returns the shared type
+    fn same_type(&self, node_id: &str, a: &str, b: &str) -> Result<Option<ValueType>, CompilerError> {
+        match (self.type_of(a), self.type_of(b)) {
+            (Some(a), Some(b)) if a != b => Err(self.mismatch(node_id, a.rust_name(), b)),
+            (a, b) => Ok(a.or(b)),
+        }
+    }
+    
+    fn mismatch(&self, node_id: &str, expected: &str, found: ValueType) -> CompilerError {
+        let position = self.order.iter()
+            .find(|n| n.id == node_id)
+            .map_or((0.0, 0.0), |n| n.position);
+        
+        CompilerError::TypeMismatch {
+            node: node_id.to_string(),
+            position,
+            expected: expected.to_string(),
+            found: found.rust_name().to_string(),
+        }
+    }
+    
+    fn field_target(node: &VisualScriptNode) -> Result<(&str, &str), CompilerError> {
+        let component_type = node.data.get("componentType")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Transform");
+        let field = node.data.get("field")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| CompilerError::CodeGeneration(format!("Node {} names no field", node.id)))?;
+        
+        Ok((component_type, field))
+    }
+    
     fn get_input(&self, node_id: &str, input_name: &str) -> Option<String> {
         self.temp_vars.get(&format!("{}_{}", node_id, input_name)).cloned()
     }
@@ -334,22 +777,10 @@ impl ScriptCompiler {
             self.indent_level -= 1;
         }
     }
-    
-    fn to_rust_name(&self, name: &str) -> String {
-        name.chars()
-            .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '_')
-            .collect::<String>()
-            .split_whitespace()
-            .map(|word| {
-                let mut chars = word.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(first) => first.to_uppercase().chain(chars).collect(),
-                }
-            })
-            .collect()
-    }
 }
 
 // Export functionality
-pub use builder::{GameCompiler, BuildTarget, BuildResult};
\ No newline at end of file
+pub use builder::{GameCompiler, BuildTarget, BuildResult, OptimizeLevel};
+pub use pak::PakWriter;
+#[cfg(test)]
+pub(crate) use builder::{run_cargo_build, web_glue_source};
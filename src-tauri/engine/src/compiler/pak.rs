@@ -0,0 +1,44 @@
+// src-tauri/engine/src/compiler/pak.rs
+use std::collections::BTreeMap;
+use crate::assets::{PakAssetKind, PAK_MAGIC, PAK_VERSION};
+
+// Builds the `assets.pak` read back by `assets::AssetPak`. Entries are
+// written in id order so identical inputs give identical paks
+#[derive(Default)]
+pub struct PakWriter {
+    entries: BTreeMap<String, (PakAssetKind, Vec<u8>)>,
+}
+
+impl PakWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // A second asset with the same id replaces the first
+    pub fn add(&mut self, id: impl Into<String>, kind: PakAssetKind, data: Vec<u8>) {
+        self.entries.insert(id.into(), (kind, data));
+    }
+    
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(PAK_MAGIC);
+        out.extend_from_slice(&PAK_VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        
+        let mut offset = 0u64;
+        for (id, (kind, data)) in &self.entries {
+            out.extend_from_slice(&(id.len() as u16).to_le_bytes());
+            out.extend_from_slice(id.as_bytes());
+            out.push(kind.to_byte());
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            offset += data.len() as u64;
+        }
+        
+        for (_, data) in self.entries.values() {
+            out.extend_from_slice(data);
+        }
+        
+        out
+    }
+}
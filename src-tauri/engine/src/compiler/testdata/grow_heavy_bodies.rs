@@ -0,0 +1,29 @@
+use dream_engine::{World, PhysicsWorld, System, Commands, EntityId};
+use dream_engine::{Transform, Sprite, RigidBody, Vec2, Vec3};
+use dream_engine::{Input, InputBinding};
+
+pub struct GrowHeavyBodiesSystem {
+    // System state
+}
+
+impl System for GrowHeavyBodiesSystem {
+    fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, commands: &mut Commands, dt: f32) {
+        // math/constant
+        // math/constant
+        // event/update
+        // flow/for_each_entity
+        let ids: Vec<EntityId> = world.query::<(&Transform, &RigidBody)>().map(|(id, _)| id).collect();
+        for entity_id in ids {
+            let Some(entity_0) = world.entity(entity_id) else { continue };
+            // component/get_field
+            let field_1 = world.get_component::<RigidBody>(entity_0).map(|c| c.mass).unwrap_or_default();
+            // logic/compare
+            let compare_2 = field_1 > 10.0;
+            // flow/branch
+            if compare_2 {
+                // component/set_field
+                if let Some(component) = world.get_component_mut::<Transform>(entity_0) { component.scale = Vec3::new(2.0, 2.0, 1.0); }
+            }
+        }
+    }
+}
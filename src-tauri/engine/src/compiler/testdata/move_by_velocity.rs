@@ -0,0 +1,27 @@
+use dream_engine::{World, PhysicsWorld, System, Commands, EntityId};
+use dream_engine::{Transform, Sprite, RigidBody, Vec2, Vec3};
+use dream_engine::{Input, InputBinding};
+
+pub struct MoverSystem {
+    // System state
+}
+
+impl System for MoverSystem {
+    fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, commands: &mut Commands, dt: f32) {
+        // event/update
+        // flow/for_each_entity
+        // math/constant
+        // math/vector_scale
+        let scaled_1 = Vec3::new(2.0, 0.0, 0.0) * dt;
+        let ids: Vec<EntityId> = world.query::<&Transform>().map(|(id, _)| id).collect();
+        for entity_id in ids {
+            let Some(entity_0) = world.entity(entity_id) else { continue };
+            // component/get_field
+            let field_2 = world.get_component::<Transform>(entity_0).map(|c| c.position).unwrap_or_default();
+            // math/add
+            let sum_3 = field_2 + scaled_1;
+            // component/set_field
+            if let Some(component) = world.get_component_mut::<Transform>(entity_0) { component.position = sum_3; }
+        }
+    }
+}
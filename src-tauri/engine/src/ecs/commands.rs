@@ -0,0 +1,91 @@
+// src-tauri/engine/src/ecs/commands.rs
+use super::{Component, Entity, World};
+
+type Command = Box<dyn FnOnce(&mut World) + Send + Sync>;
+type SpawnInsert = Box<dyn FnOnce(&mut World, Entity) + Send + Sync>;
+
+// Structural changes recorded while a system runs and applied to the world
+// once it returns, so spawning/despawning never fights a live query borrow
+#[derive(Default)]
+pub struct Commands {
+    queue: Vec<Command>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Queued when the returned builder is dropped, e.g.
+    // `commands.spawn().with(Transform::default());`
+    pub fn spawn(&mut self) -> SpawnCommand<'_> {
+        SpawnCommand {
+            commands: self,
+            components: Vec::new(),
+        }
+    }
+    
+    pub fn despawn(&mut self, entity: Entity) {
+        self.push(move |world| {
+            world.destroy_entity(entity);
+        });
+    }
+    
+    pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
+        self.push(move |world| {
+            world.add_component(entity, component);
+        });
+    }
+    
+    pub fn push<F>(&mut self, command: F)
+    where
+        F: FnOnce(&mut World) + Send + Sync + 'static,
+    {
+        self.queue.push(Box::new(command));
+    }
+    
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+    
+    // Runs every queued command in the order it was recorded
+    pub fn apply(&mut self, world: &mut World) {
+        for command in self.queue.drain(..) {
+            command(world);
+        }
+    }
+}
+
+pub struct SpawnCommand<'c> {
+    commands: &'c mut Commands,
+    components: Vec<SpawnInsert>,
+}
+
+impl<'c> SpawnCommand<'c> {
+    pub fn with<T: Component>(mut self, component: T) -> Self {
+        self.components.push(Box::new(move |world: &mut World, entity: Entity| {
+            world.add_component(entity, component);
+        }));
+        self
+    }
+}
+
+impl<'c> Drop for SpawnCommand<'c> {
+    fn drop(&mut self) {
+        let components = std::mem::take(&mut self.components);
+        // A full world drops the spawn rather than panicking mid-frame
+        self.commands.push(move |world| {
+            let Some(entity) = world.try_create_entity() else {
+                log::warn!("Spawn dropped: world is at max_entities ({:?})", world.max_entities());
+                return;
+            };
+            for insert in components {
+                insert(world, entity);
+            }
+        });
+    }
+}
@@ -14,12 +14,16 @@ pub trait ComponentVec: Send + Sync {
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn remove(&mut self, entity: EntityId);
     fn clear(&mut self);
+    fn clear_changed(&mut self);
+    fn type_name(&self) -> &'static str;
 }
 
 pub struct TypedComponentVec<T: Component> {
     components: Vec<Option<T>>,
     entities: Vec<EntityId>,
     entity_indices: HashMap<EntityId, usize>,
+    // Per-slot flag set whenever a component is inserted or borrowed mutably
+    changed: Vec<bool>,
 }
 
 impl<T: Component> TypedComponentVec<T> {
@@ -28,17 +32,22 @@ impl<T: Component> TypedComponentVec<T> {
             components: Vec::new(),
             entities: Vec::new(),
             entity_indices: HashMap::new(),
+            changed: Vec::new(),
         }
     }
     
-    pub fn insert(&mut self, entity: EntityId, component: T) {
+    // Replaces any component the entity already had and returns it
+    pub fn insert(&mut self, entity: EntityId, component: T) -> Option<T> {
         if let Some(&idx) = self.entity_indices.get(&entity) {
-            self.components[idx] = Some(component);
+            self.changed[idx] = true;
+            self.components[idx].replace(component)
         } else {
             let idx = self.entities.len();
             self.entities.push(entity);
             self.components.push(Some(component));
+            self.changed.push(true);
             self.entity_indices.insert(entity, idx);
+            None
         }
     }
     
@@ -50,10 +59,10 @@ impl<T: Component> TypedComponentVec<T> {
     }
     
     pub fn get_mut(&mut self, entity: EntityId) -> Option<&mut T> {
-        self.entity_indices
-            .get(&entity)
-            .and_then(|&idx| self.components.get_mut(idx))
-            .and_then(|c| c.as_mut())
+        let idx = *self.entity_indices.get(&entity)?;
+        let component = self.components.get_mut(idx)?.as_mut()?;
+        self.changed[idx] = true;
+        Some(component)
     }
     
     pub fn remove(&mut self, entity: EntityId) -> Option<T> {
@@ -63,6 +72,7 @@ impl<T: Component> TypedComponentVec<T> {
             if idx != last_idx {
                 self.entities.swap(idx, last_idx);
                 self.components.swap(idx, last_idx);
+                self.changed.swap(idx, last_idx);
                 
                 // Update the swapped entity's index
                 let swapped_entity = self.entities[idx];
@@ -70,22 +80,63 @@ impl<T: Component> TypedComponentVec<T> {
             }
             
             self.entities.pop();
+            self.changed.pop();
             self.components.pop().unwrap()
         } else {
             None
         }
     }
     
+    pub fn contains(&self, entity: EntityId) -> bool {
+        self.entity_indices.contains_key(&entity)
+    }
+    
+    pub fn entities(&self) -> &[EntityId] {
+        &self.entities
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+    
     pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
         self.entities.iter()
             .zip(self.components.iter())
             .filter_map(|(&e, c)| c.as_ref().map(|c| (e, c)))
     }
     
+    // Every yielded slot is flagged, since we can't tell which ones get written
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut T)> {
         self.entities.iter()
             .zip(self.components.iter_mut())
-            .filter_map(|(&e, c)| c.as_mut().map(|c| (e, c)))
+            .zip(self.changed.iter_mut())
+            .filter_map(|((&e, c), changed)| {
+                let c = c.as_mut()?;
+                *changed = true;
+                Some((e, c))
+            })
+    }
+    
+    pub fn is_changed(&self, entity: EntityId) -> bool {
+        self.entity_indices
+            .get(&entity)
+            .is_some_and(|&idx| self.changed[idx])
+    }
+    
+    pub fn changed(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        self.entities.iter()
+            .zip(self.components.iter())
+            .zip(self.changed.iter())
+            .filter(|(_, &changed)| changed)
+            .filter_map(|((&e, c), _)| c.as_ref().map(|c| (e, c)))
+    }
+    
+    pub fn clear_changed(&mut self) {
+        self.changed.fill(false);
     }
 }
 
@@ -101,22 +152,41 @@ impl<T: Component> ComponentVec for TypedComponentVec<T> {
         self.components.clear();
         self.entities.clear();
         self.entity_indices.clear();
+        self.changed.clear();
+    }
+    
+    fn clear_changed(&mut self) {
+        self.clear_changed();
+    }
+    
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
     }
 }
 
 pub struct ComponentStorage {
     storages: HashMap<TypeId, Box<dyn ComponentVec>>,
+    // Storage types sorted by type name, so bulk removal (and any Drop side
+    // effects) runs in the same order regardless of HashMap layout
+    removal_order: Vec<(&'static str, TypeId)>,
 }
 
 impl ComponentStorage {
     pub fn new() -> Self {
         Self {
             storages: HashMap::new(),
+            removal_order: Vec::new(),
         }
     }
     
-    pub fn insert<T: Component>(&mut self, entity: EntityId, component: T) {
+    pub fn insert<T: Component>(&mut self, entity: EntityId, component: T) -> Option<T> {
         let type_id = T::type_id();
+        if !self.storages.contains_key(&type_id) {
+            let key = (std::any::type_name::<T>(), type_id);
+            let idx = self.removal_order.partition_point(|&(name, _)| name < key.0);
+            self.removal_order.insert(idx, key);
+        }
+        
         let storage = self.storages
             .entry(type_id)
             .or_insert_with(|| Box::new(TypedComponentVec::<T>::new()));
@@ -126,7 +196,7 @@ impl ComponentStorage {
             .downcast_mut::<TypedComponentVec<T>>()
             .unwrap();
         
-        typed_storage.insert(entity, component);
+        typed_storage.insert(entity, component)
     }
     
     pub fn remove<T: Component>(&mut self, entity: EntityId) -> Option<T> {
@@ -162,6 +232,15 @@ impl ComponentStorage {
             })
     }
     
+    pub fn contains<T: Component>(&self, entity: EntityId) -> bool {
+        self.get_storage::<T>().is_some_and(|storage| storage.contains(entity))
+    }
+    
+    // In storage order
+    pub fn entities<T: Component>(&self) -> &[EntityId] {
+        self.get_storage::<T>().map_or(&[], |storage| storage.entities())
+    }
+    
     pub fn get_storage<T: Component>(&self) -> Option<&TypedComponentVec<T>> {
         let type_id = T::type_id();
         self.storages.get(&type_id)
@@ -175,14 +254,24 @@ impl ComponentStorage {
     }
     
     pub fn remove_all(&mut self, entity: EntityId) {
-        for storage in self.storages.values_mut() {
-            storage.remove(entity);
+        for (_, type_id) in &self.removal_order {
+            if let Some(storage) = self.storages.get_mut(type_id) {
+                storage.remove(entity);
+            }
         }
     }
     
     pub fn clear(&mut self) {
+        for (_, type_id) in &self.removal_order {
+            if let Some(storage) = self.storages.get_mut(type_id) {
+                storage.clear();
+            }
+        }
+    }
+    
+    pub fn clear_changed(&mut self) {
         for storage in self.storages.values_mut() {
-            storage.clear();
+            storage.clear_changed();
         }
     }
 }
\ No newline at end of file
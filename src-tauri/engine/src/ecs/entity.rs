@@ -0,0 +1,17 @@
+// src-tauri/engine/src/ecs/entity.rs
+use serde::{Deserialize, Serialize};
+use super::EntityId;
+
+// A handle to a live entity. The generation changes whenever the id slot is
+// recycled, so a handle kept past `destroy_entity` stops resolving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: EntityId,
+    pub generation: u32,
+}
+
+impl Entity {
+    pub fn new(id: EntityId, generation: u32) -> Self {
+        Self { id, generation }
+    }
+}
@@ -0,0 +1,91 @@
+// src-tauri/engine/src/ecs/events.rs
+use std::marker::PhantomData;
+
+// Double-buffered event queue, kept as a world resource. `update` is called
+// once per step: events sent since the last update move to the previous
+// buffer and the ones before that are dropped, so a reader that runs every
+// step sees each event exactly once
+pub struct Events<T> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    // Sequence numbers of the first event in each buffer
+    previous_start: usize,
+    current_start: usize,
+    sent: usize,
+}
+
+impl<T> Events<T> {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+            current_start: 0,
+            sent: 0,
+        }
+    }
+    
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+        self.sent += 1;
+    }
+    
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+        self.previous_start = self.current_start;
+        self.current_start = self.sent;
+    }
+    
+    // A reader that skips anything sent before the last `update`, so systems
+    // added mid-game don't act on stale events
+    pub fn reader(&self) -> EventReader<T> {
+        EventReader {
+            next: self.current_start,
+            _marker: PhantomData,
+        }
+    }
+    
+    // Events still buffered, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.previous.iter().chain(self.current.iter())
+    }
+    
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for Events<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, events: I) {
+        for event in events {
+            self.send(event);
+        }
+    }
+}
+
+// Each consumer keeps its own cursor, so any number of systems can read the
+// same events independently
+pub struct EventReader<T> {
+    next: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> EventReader<T> {
+    // Everything sent since this reader last read. Events that already left
+    // both buffers are skipped
+    pub fn read<'a>(&mut self, events: &'a Events<T>) -> impl Iterator<Item = &'a T> {
+        let skip = self.next.saturating_sub(events.previous_start);
+        self.next = events.sent;
+        events.iter().skip(skip)
+    }
+}
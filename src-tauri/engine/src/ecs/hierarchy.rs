@@ -0,0 +1,216 @@
+// src-tauri/engine/src/ecs/hierarchy.rs
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::math::Transform;
+use super::{Component, Entity, EntityId, World};
+
+// An entity's `Transform` is relative to its parent's world transform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Parent(pub Entity);
+
+impl Component for Parent {}
+
+// Kept in sync with `Parent` by `World::set_parent`; don't edit directly
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Children(pub Vec<Entity>);
+
+impl Component for Children {}
+
+// World-space transform written by `World::propagate_transforms` after the
+// systems run; the renderer draws with it when present
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GlobalTransform(pub Transform);
+
+impl Component for GlobalTransform {}
+
+// World-space transform as of the start of the latest fixed step, written by
+// `World::store_previous_transforms`. The renderer blends from it to the
+// current transform so motion stays smooth between steps
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PreviousTransform(pub Transform);
+
+impl Component for PreviousTransform {}
+
+impl World {
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.get_component::<Parent>(entity).map(|p| p.0)
+    }
+    
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.get_component::<Children>(entity).map(|c| c.0.as_slice()).unwrap_or(&[])
+    }
+    
+    // Composes local transforms up the parent chain
+    pub fn world_transform(&self, entity: Entity) -> Transform {
+        let mut world = self.get_component::<Transform>(entity).copied().unwrap_or_default();
+        let mut current = self.parent(entity);
+        
+        while let Some(parent) = current {
+            let local = self.get_component::<Transform>(parent).copied().unwrap_or_default();
+            world = local.mul_transform(&world);
+            current = self.parent(parent);
+        }
+        
+        world
+    }
+    
+    // Writes a `GlobalTransform` for every entity with a `Transform`,
+    // resolving each parent once. Entities whose `Parent` chain loops (only
+    // possible by inserting `Parent` directly) and their descendants lose
+    // their `GlobalTransform` instead
+    pub fn propagate_transforms(&mut self) {
+        let ids: Vec<EntityId> = self.query::<&Transform>().map(|(id, _)| id).collect();
+        let mut resolved = HashMap::new();
+        let mut path = Vec::new();
+        
+        for &id in &ids {
+            self.resolve_global(id, &mut resolved, &mut path);
+        }
+        
+        for id in ids {
+            let Some(entity) = self.entity(id) else { continue };
+            match resolved.get(&id).copied().flatten() {
+                Some(global) => {
+                    self.add_component(entity, GlobalTransform(global));
+                }
+                None => {
+                    self.remove_component::<GlobalTransform>(entity);
+                }
+            }
+        }
+    }
+    
+    // Entities spawned since the last propagation have no `GlobalTransform`
+    // yet, so their local transform stands in
+    pub fn store_previous_transforms(&mut self) {
+        let previous: Vec<(EntityId, Transform)> = self.query::<&Transform>()
+            .map(|(id, transform)| {
+                let global = self.entity(id).and_then(|e| self.get_component::<GlobalTransform>(e));
+                (id, global.map_or(*transform, |global| global.0))
+            })
+            .collect();
+        
+        for (id, transform) in previous {
+            if let Some(entity) = self.entity(id) {
+                self.add_component(entity, PreviousTransform(transform));
+            }
+        }
+    }
+    
+    // `None` marks an entity caught in (or under) a parent cycle
+    fn resolve_global(
+        &self,
+        id: EntityId,
+        resolved: &mut HashMap<EntityId, Option<Transform>>,
+        path: &mut Vec<EntityId>
+    ) -> Option<Transform> {
+        if let Some(&global) = resolved.get(&id) {
+            return global;
+        }
+        
+        if let Some(start) = path.iter().position(|&e| e == id) {
+            log::warn!("Parent cycle through entities {:?}; skipping their transforms", &path[start..]);
+            for &e in &path[start..] {
+                resolved.insert(e, None);
+            }
+            return None;
+        }
+        
+        let entity = self.entity(id)?;
+        let local = self.get_component::<Transform>(entity).copied().unwrap_or_default();
+        
+        path.push(id);
+        let global = match self.parent(entity).filter(|&parent| self.is_alive(parent)) {
+            Some(parent) => self.resolve_global(parent.id, resolved, path)
+                .map(|parent_global| parent_global.mul_transform(&local)),
+            None => Some(local),
+        };
+        path.pop();
+        
+        // Cycle members were already marked while unwinding
+        *resolved.entry(id).or_insert(global)
+    }
+    
+    // Moves `child` under `new_parent` (or to the root with `None`). With
+    // `keep_world_transform` the local transform is rewritten so the child
+    // stays where it is; otherwise it keeps its local values and moves with
+    // the new parent. Refuses to create a cycle
+    pub fn set_parent(&mut self, child: Entity, new_parent: Option<Entity>, keep_world_transform: bool) -> bool {
+        if !self.is_alive(child) {
+            return false;
+        }
+        
+        if let Some(parent) = new_parent {
+            if !self.is_alive(parent) || self.is_descendant_or_self(parent, child) {
+                return false;
+            }
+        }
+        
+        let world = keep_world_transform.then(|| self.world_transform(child));
+        
+        if let Some(old_parent) = self.parent(child) {
+            if let Some(children) = self.get_component_mut::<Children>(old_parent) {
+                children.0.retain(|&c| c != child);
+            }
+        }
+        
+        match new_parent {
+            Some(parent) => {
+                self.add_component(child, Parent(parent));
+                match self.get_component_mut::<Children>(parent) {
+                    Some(children) => children.0.push(child),
+                    None => {
+                        self.add_component(parent, Children(vec![child]));
+                    }
+                }
+            }
+            None => {
+                self.remove_component::<Parent>(child);
+            }
+        }
+        
+        if let Some(world) = world {
+            let local = match new_parent {
+                Some(parent) => self.world_transform(parent).inverse().mul_transform(&world),
+                None => world,
+            };
+            self.add_component(child, local);
+        }
+        
+        true
+    }
+    
+    // Destroys `entity` and everything below it
+    pub fn despawn_recursive(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        
+        if let Some(parent) = self.parent(entity) {
+            if let Some(children) = self.get_component_mut::<Children>(parent) {
+                children.0.retain(|&c| c != entity);
+            }
+        }
+        
+        let mut stack = vec![entity];
+        while let Some(current) = stack.pop() {
+            stack.extend_from_slice(self.children(current));
+            self.destroy_entity(current);
+        }
+        
+        true
+    }
+    
+    fn is_descendant_or_self(&self, entity: Entity, ancestor: Entity) -> bool {
+        let mut current = Some(entity);
+        
+        while let Some(e) = current {
+            if e == ancestor {
+                return true;
+            }
+            current = self.parent(e);
+        }
+        
+        false
+    }
+}
@@ -1,12 +1,26 @@
 // src-tauri/engine/src/ecs/mod.rs
 mod world;
+mod entity;
 mod component;
 mod system;
 mod query;
+mod commands;
+mod snapshot;
+mod pool;
+mod hierarchy;
+mod spatial_index;
+mod events;
 
 pub use world::*;
+pub use entity::*;
 pub use component::*;
 pub use system::*;
 pub use query::*;
+pub use commands::*;
+pub use snapshot::{WorldDiff, ComponentChange, SavedScene, SavedEntity, SceneError};
+pub use pool::*;
+pub use hierarchy::*;
+pub use spatial_index::*;
+pub use events::*;
 
 pub type EntityId = u32;
\ No newline at end of file
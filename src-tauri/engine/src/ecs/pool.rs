@@ -0,0 +1,141 @@
+// src-tauri/engine/src/ecs/pool.rs
+use std::collections::VecDeque;
+use std::sync::Arc;
+use super::{Commands, Entity, World};
+
+type Layout = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+// What `acquire` does once every pooled entity is in use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolPolicy {
+    // Return None
+    Fail,
+    // Create another entity and keep it in the pool from then on, or return
+    // None if the world is full
+    Grow,
+    // Take back the entity that has been active longest
+    RecycleOldest,
+}
+
+// Pre-allocated entities for things like bullets and particles. Released
+// entities stay alive with no components (so no query sees them) and get a
+// fresh copy of the layout's components when acquired again
+pub struct EntityPool {
+    available: VecDeque<Entity>,
+    active: VecDeque<Entity>,
+    layout: Layout,
+    policy: PoolPolicy,
+}
+
+impl EntityPool {
+    // Pre-allocates as many of `capacity` entities as the world has room for;
+    // `capacity()` tells how many that was
+    pub fn new<F>(world: &mut World, capacity: usize, layout: F) -> Self
+    where
+        F: Fn(&mut World, Entity) + Send + Sync + 'static,
+    {
+        let available: VecDeque<Entity> = (0..capacity).map_while(|_| world.try_create_entity()).collect();
+        if available.len() < capacity {
+            log::warn!("World is full; entity pool holds {} of {} entities", available.len(), capacity);
+        }
+        
+        Self {
+            available,
+            active: VecDeque::new(),
+            layout: Arc::new(layout),
+            policy: PoolPolicy::Fail,
+        }
+    }
+    
+    pub fn with_policy(mut self, policy: PoolPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    
+    pub fn acquire(&mut self, world: &mut World) -> Option<Entity> {
+        let entity = match self.available.pop_front() {
+            Some(entity) => entity,
+            None => match self.policy {
+                PoolPolicy::Fail => return None,
+                PoolPolicy::Grow => world.try_create_entity()?,
+                PoolPolicy::RecycleOldest => self.active.pop_front()?,
+            },
+        };
+        
+        world.remove_all_components(entity);
+        (self.layout)(world, entity);
+        self.active.push_back(entity);
+        
+        Some(entity)
+    }
+    
+    pub fn release(&mut self, world: &mut World, entity: Entity) -> bool {
+        if !self.take_active(entity) {
+            return false;
+        }
+        
+        world.remove_all_components(entity);
+        self.available.push_back(entity);
+        true
+    }
+    
+    // Picks the entity now and queues the component reset, for use inside a
+    // system while queries are borrowed. `Grow` can't allocate through the
+    // buffer, so it behaves like `Fail` here
+    pub fn acquire_deferred(&mut self, commands: &mut Commands) -> Option<Entity> {
+        let entity = match self.available.pop_front() {
+            Some(entity) => entity,
+            None => match self.policy {
+                PoolPolicy::Fail | PoolPolicy::Grow => return None,
+                PoolPolicy::RecycleOldest => self.active.pop_front()?,
+            },
+        };
+        
+        let layout = self.layout.clone();
+        commands.push(move |world| {
+            world.remove_all_components(entity);
+            layout(world, entity);
+        });
+        self.active.push_back(entity);
+        
+        Some(entity)
+    }
+    
+    pub fn release_deferred(&mut self, commands: &mut Commands, entity: Entity) -> bool {
+        if !self.take_active(entity) {
+            return false;
+        }
+        
+        commands.push(move |world| {
+            world.remove_all_components(entity);
+        });
+        self.available.push_back(entity);
+        true
+    }
+    
+    pub fn is_active(&self, entity: Entity) -> bool {
+        self.active.contains(&entity)
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.available.len() + self.active.len()
+    }
+    
+    pub fn available(&self) -> usize {
+        self.available.len()
+    }
+    
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+    
+    fn take_active(&mut self, entity: Entity) -> bool {
+        match self.active.iter().position(|&e| e == entity) {
+            Some(idx) => {
+                self.active.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+}
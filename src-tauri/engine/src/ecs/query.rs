@@ -2,6 +2,10 @@
 use super::{Component, ComponentStorage, EntityId};
 use std::marker::PhantomData;
 
+// Queries yield entities in ascending `EntityId` order, not storage order.
+// Storage order depends on the history of removals (they swap the last
+// element into the hole), so sorting keeps systems that iterate and mutate
+// deterministic for seeded replays and lockstep runs
 pub trait Query {
     type Iter<'a>;
     type IterMut<'a>;
@@ -16,8 +20,15 @@ impl<T: Component> Query for &T {
     type IterMut<'a> = SingleComponentIterMut<'a, T>;
     
     fn query(storage: &ComponentStorage) -> Self::Iter<'_> {
+        let storage = storage.get_storage::<T>();
+        let mut entities: Vec<EntityId> = storage
+            .map(|s| s.entities().to_vec())
+            .unwrap_or_default();
+        entities.sort_unstable();
+        
         SingleComponentIter {
-            storage: storage.get_storage::<T>(),
+            storage,
+            entities,
             index: 0,
         }
     }
@@ -32,6 +43,7 @@ impl<T: Component> Query for &T {
 
 pub struct SingleComponentIter<'a, T: Component> {
     storage: Option<&'a super::TypedComponentVec<T>>,
+    entities: Vec<EntityId>,
     index: usize,
 }
 
@@ -39,16 +51,17 @@ impl<'a, T: Component> Iterator for SingleComponentIter<'a, T> {
     type Item = (EntityId, &'a T);
     
     fn next(&mut self) -> Option<Self::Item> {
-        self.storage.and_then(|s| {
-            let entities: Vec<_> = s.iter().collect();
-            if self.index < entities.len() {
-                let result = entities[self.index];
-                self.index += 1;
-                Some(result)
-            } else {
-                None
+        let storage = self.storage?;
+        while self.index < self.entities.len() {
+            let entity = self.entities[self.index];
+            self.index += 1;
+            
+            if let Some(component) = storage.get(entity) {
+                return Some((entity, component));
             }
-        })
+        }
+        
+        None
     }
 }
 
@@ -72,15 +85,32 @@ impl<A: Component, B: Component> Query for (&A, &B) {
     type IterMut<'a> = TupleComponentIterMut<'a, A, B>;
     
     fn query(storage: &ComponentStorage) -> Self::Iter<'_> {
+        let storage_a = storage.get_storage::<A>();
+        let storage_b = storage.get_storage::<B>();
+        
+        // Walk the smaller storage and confirm membership in the other
+        let mut entities: Vec<EntityId> = match (storage_a, storage_b) {
+            (Some(a), Some(b)) => {
+                let smallest = if a.len() <= b.len() { a.entities() } else { b.entities() };
+                
+                smallest.iter()
+                    .copied()
+                    .filter(|&e| a.contains(e) && b.contains(e))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        entities.sort_unstable();
+        
         TupleComponentIter {
-            storage_a: storage.get_storage::<A>(),
-            storage_b: storage.get_storage::<B>(),
-            entities: Vec::new(),
+            storage_a,
+            storage_b,
+            entities,
             index: 0,
         }
     }
     
-    fn query_mut(storage: &mut ComponentStorage) -> Self::IterMut<'_> {
+    fn query_mut(_storage: &mut ComponentStorage) -> Self::IterMut<'_> {
         TupleComponentIterMut {
             _phantom: PhantomData,
         }
@@ -98,7 +128,18 @@ impl<'a, A: Component, B: Component> Iterator for TupleComponentIter<'a, A, B> {
     type Item = (EntityId, (&'a A, &'a B));
     
     fn next(&mut self) -> Option<Self::Item> {
-        // Implementation would find entities that have both components
+        while self.index < self.entities.len() {
+            let entity = self.entities[self.index];
+            self.index += 1;
+            
+            let a = self.storage_a.and_then(|s| s.get(entity));
+            let b = self.storage_b.and_then(|s| s.get(entity));
+            
+            if let (Some(a), Some(b)) = (a, b) {
+                return Some((entity, (a, b)));
+            }
+        }
+        
         None
     }
 }
@@ -113,4 +154,178 @@ impl<'a, A: Component, B: Component> Iterator for TupleComponentIterMut<'a, A, B
     fn next(&mut self) -> Option<Self::Item> {
         None
     }
-}
\ No newline at end of file
+}
+
+// Query for three components
+impl<A: Component, B: Component, C: Component> Query for (&A, &B, &C) {
+    type Iter<'a> = TripleComponentIter<'a, A, B, C>;
+    type IterMut<'a> = TripleComponentIterMut<'a, A, B, C>;
+    
+    fn query(storage: &ComponentStorage) -> Self::Iter<'_> {
+        let storage_a = storage.get_storage::<A>();
+        let storage_b = storage.get_storage::<B>();
+        let storage_c = storage.get_storage::<C>();
+        
+        // Walk the smallest storage and confirm membership in the other two
+        let mut entities: Vec<EntityId> = match (storage_a, storage_b, storage_c) {
+            (Some(a), Some(b), Some(c)) => {
+                let smallest = [a.entities(), b.entities(), c.entities()]
+                    .into_iter()
+                    .min_by_key(|entities| entities.len())
+                    .unwrap_or(&[]);
+                
+                smallest.iter()
+                    .copied()
+                    .filter(|&e| a.contains(e) && b.contains(e) && c.contains(e))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        entities.sort_unstable();
+        
+        TripleComponentIter {
+            storage_a,
+            storage_b,
+            storage_c,
+            entities,
+            index: 0,
+        }
+    }
+    
+    fn query_mut(_storage: &mut ComponentStorage) -> Self::IterMut<'_> {
+        TripleComponentIterMut {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pub struct TripleComponentIter<'a, A: Component, B: Component, C: Component> {
+    storage_a: Option<&'a super::TypedComponentVec<A>>,
+    storage_b: Option<&'a super::TypedComponentVec<B>>,
+    storage_c: Option<&'a super::TypedComponentVec<C>>,
+    entities: Vec<EntityId>,
+    index: usize,
+}
+
+impl<'a, A: Component, B: Component, C: Component> Iterator for TripleComponentIter<'a, A, B, C> {
+    type Item = (EntityId, (&'a A, &'a B, &'a C));
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.entities.len() {
+            let entity = self.entities[self.index];
+            self.index += 1;
+            
+            let a = self.storage_a.and_then(|s| s.get(entity));
+            let b = self.storage_b.and_then(|s| s.get(entity));
+            let c = self.storage_c.and_then(|s| s.get(entity));
+            
+            if let (Some(a), Some(b), Some(c)) = (a, b, c) {
+                return Some((entity, (a, b, c)));
+            }
+        }
+        
+        None
+    }
+}
+
+pub struct TripleComponentIterMut<'a, A: Component, B: Component, C: Component> {
+    _phantom: PhantomData<(&'a A, &'a B, &'a C)>,
+}
+
+impl<'a, A: Component, B: Component, C: Component> Iterator for TripleComponentIterMut<'a, A, B, C> {
+    type Item = (EntityId, (&'a mut A, &'a mut B, &'a mut C));
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        None
+    }
+}
+// Filters go after the components in a query tuple and narrow the result
+// without yielding anything, e.g. `query::<(&Transform, With<Player>,
+// Without<Frozen>)>()` yields `(EntityId, &Transform)`
+pub struct With<T: Component>(PhantomData<T>);
+pub struct Without<T: Component>(PhantomData<T>);
+
+pub trait QueryFilter {
+    fn matches(storage: &ComponentStorage, entity: EntityId) -> bool;
+}
+
+impl<T: Component> QueryFilter for With<T> {
+    fn matches(storage: &ComponentStorage, entity: EntityId) -> bool {
+        storage.contains::<T>(entity)
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    fn matches(storage: &ComponentStorage, entity: EntityId) -> bool {
+        !storage.contains::<T>(entity)
+    }
+}
+
+// Wraps the unfiltered query's iterator, passing through only the entities
+// that were matched up front. Both are in ascending order
+pub struct FilteredIter<I> {
+    inner: I,
+    entities: Vec<EntityId>,
+}
+
+impl<I, T> Iterator for FilteredIter<I> where I: Iterator<Item = (EntityId, T)> {
+    type Item = (EntityId, T);
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entity, item) = self.inner.next()?;
+            if self.entities.binary_search(&entity).is_ok() {
+                return Some((entity, item));
+            }
+        }
+    }
+}
+
+macro_rules! impl_filtered_query {
+    ($base:ty; $($component:ident),+; $($filter:ident<$marker:ident>),+) => {
+        impl<$($component: Component,)+ $($marker: Component,)+> Query for ($(&$component,)+ $($filter<$marker>,)+) {
+            type Iter<'a> = FilteredIter<<$base as Query>::Iter<'a>>;
+            type IterMut<'a> = FilteredIter<<$base as Query>::IterMut<'a>>;
+            
+            fn query(storage: &ComponentStorage) -> Self::Iter<'_> {
+                let entities = <$base as Query>::query(storage)
+                    .map(|(entity, _)| entity)
+                    .filter(|&entity| $(<$filter<$marker> as QueryFilter>::matches(storage, entity))&&+)
+                    .collect();
+                
+                FilteredIter {
+                    inner: <$base as Query>::query(storage),
+                    entities,
+                }
+            }
+            
+            fn query_mut(storage: &mut ComponentStorage) -> Self::IterMut<'_> {
+                let entities = <$base as Query>::query(storage)
+                    .map(|(entity, _)| entity)
+                    .filter(|&entity| $(<$filter<$marker> as QueryFilter>::matches(storage, entity))&&+)
+                    .collect();
+                
+                FilteredIter {
+                    inner: <$base as Query>::query_mut(storage),
+                    entities,
+                }
+            }
+        }
+    };
+}
+
+// One or two filters of either kind after one to three components
+macro_rules! impl_filtered_queries {
+    ($base:ty; $($component:ident),+) => {
+        impl_filtered_query!($base; $($component),+; With<F1>);
+        impl_filtered_query!($base; $($component),+; Without<F1>);
+        impl_filtered_query!($base; $($component),+; With<F1>, With<F2>);
+        impl_filtered_query!($base; $($component),+; With<F1>, Without<F2>);
+        impl_filtered_query!($base; $($component),+; Without<F1>, With<F2>);
+        impl_filtered_query!($base; $($component),+; Without<F1>, Without<F2>);
+    };
+}
+
+impl_filtered_queries!(&'static A; A);
+impl_filtered_queries!((&'static A, &'static B); A, B);
+impl_filtered_queries!((&'static A, &'static B, &'static C); A, B, C);
@@ -0,0 +1,125 @@
+// src-tauri/engine/src/ecs/snapshot.rs
+use std::any::TypeId;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use super::{Component, ComponentStorage, EntityId};
+
+type FromJson = fn(&mut ComponentStorage, EntityId, Value) -> Result<(), serde_json::Error>;
+
+// Type-erased JSON view of one component type, registered per world so
+// worlds can be inspected and compared without knowing their component types
+#[derive(Clone, Copy)]
+pub(super) struct Inspector {
+    pub name: &'static str,
+    pub type_id: TypeId,
+    pub to_json: fn(&ComponentStorage, EntityId) -> Option<Value>,
+    // Set for types registered with `register_serializable`; only those are
+    // written to and read back from scenes
+    pub from_json: Option<FromJson>,
+}
+
+impl Inspector {
+    pub fn of<T: Component + Serialize>() -> Self {
+        Self {
+            name: short_type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+            to_json: |storage, entity| {
+                storage.get::<T>(entity).and_then(|c| serde_json::to_value(c).ok())
+            },
+            from_json: None,
+        }
+    }
+    
+    pub fn serializable<T: Component + Serialize + DeserializeOwned>() -> Self {
+        Self {
+            from_json: Some(|storage, entity, json| {
+                storage.insert(entity, serde_json::from_value::<T>(json)?);
+                Ok(())
+            }),
+            ..Self::of::<T>()
+        }
+    }
+}
+
+// Last path segment, or the whole path for generic types whose last segment
+// would only be the tail of a type argument
+pub(super) fn short_type_name<T>() -> &'static str {
+    let full = std::any::type_name::<T>();
+    if full.contains('<') {
+        return full;
+    }
+    full.rsplit("::").next().unwrap_or(full)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentChange {
+    pub entity: EntityId,
+    pub component: &'static str,
+    // None when the component is missing on that side
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+// Result of `World::diff(other)`, read as "what it takes to turn self into other"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldDiff {
+    pub added: Vec<EntityId>,
+    pub removed: Vec<EntityId>,
+    pub changed: Vec<ComponentChange>,
+}
+
+impl WorldDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+// A saved world: every live entity with its serializable components, by
+// registered name. Ids and generations are kept so stored handles stay valid
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedScene {
+    pub entities: Vec<SavedEntity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedEntity {
+    pub id: EntityId,
+    pub generation: u32,
+    pub components: Vec<(String, Value)>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SceneError {
+    #[error("Entity {entity} has unknown component {component}; register it with register_serializable")]
+    UnknownComponent { entity: EntityId, component: String },
+    
+    #[error("Entity {entity} has an invalid {component}: {message}")]
+    InvalidComponent { entity: EntityId, component: String, message: String },
+    
+    #[error("Entity {0} appears more than once")]
+    DuplicateEntity(EntityId),
+    
+    #[error("Scene has {count} entities but the world holds at most {max}")]
+    TooManyEntities { count: usize, max: usize },
+    
+    #[error("Component name {0} is already used by another serializable type; register one with register_serializable_as")]
+    DuplicateComponentName(String),
+}
+
+// Structural JSON equality where numbers may differ by up to `epsilon`
+pub(super) fn json_approx_eq(a: &Value, b: &Value, epsilon: f64) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => x == y || (x - y).abs() <= epsilon,
+            _ => x == y,
+        },
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| json_approx_eq(x, y, epsilon))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| json_approx_eq(v, w, epsilon)))
+        }
+        _ => a == b,
+    }
+}
@@ -0,0 +1,156 @@
+// src-tauri/engine/src/ecs/spatial_index.rs
+use std::collections::HashMap;
+use crate::math::{Transform, Vec2};
+use super::{EntityId, World};
+
+// Uniform grid over entity positions for gameplay lookups (radius, nearest).
+// Unlike the physics broad phase it holds points, not collider bounds
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<EntityId>>,
+    positions: HashMap<EntityId, Vec2>,
+    // Occupied cell range, so ring searches know when to give up
+    min_cell: (i32, i32),
+    max_cell: (i32, i32),
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            positions: HashMap::new(),
+            min_cell: (i32::MAX, i32::MAX),
+            max_cell: (i32::MIN, i32::MIN),
+        }
+    }
+    
+    // Indexes every entity with a Transform by its x/y position
+    pub fn from_world(world: &World, cell_size: f32) -> Self {
+        let mut index = Self::new(cell_size);
+        for (entity, transform) in world.query::<&Transform>() {
+            index.insert(entity, transform.position.xy());
+        }
+        index
+    }
+    
+    pub fn insert(&mut self, entity: EntityId, position: Vec2) {
+        self.remove(entity);
+        
+        let cell = self.cell_of(position);
+        self.cells.entry(cell).or_default().push(entity);
+        self.positions.insert(entity, position);
+        
+        self.min_cell = (self.min_cell.0.min(cell.0), self.min_cell.1.min(cell.1));
+        self.max_cell = (self.max_cell.0.max(cell.0), self.max_cell.1.max(cell.1));
+    }
+    
+    pub fn remove(&mut self, entity: EntityId) -> bool {
+        let Some(position) = self.positions.remove(&entity) else {
+            return false;
+        };
+        
+        let cell = self.cell_of(position);
+        if let Some(bucket) = self.cells.get_mut(&cell) {
+            bucket.retain(|&e| e != entity);
+            if bucket.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+        true
+    }
+    
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<EntityId> {
+        let (min_x, min_y) = self.cell_of(center - Vec2::splat(radius));
+        let (max_x, max_y) = self.cell_of(center + Vec2::splat(radius));
+        let radius_squared = radius * radius;
+        let mut found = Vec::new();
+        
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                for &entity in self.cells.get(&(x, y)).into_iter().flatten() {
+                    if (self.positions[&entity] - center).length_squared() <= radius_squared {
+                        found.push(entity);
+                    }
+                }
+            }
+        }
+        
+        found
+    }
+    
+    // Up to `n` entities accepted by `filter`, closest first. Searches rings of
+    // cells outward and stops once no unvisited cell could hold anything closer
+    pub fn query_nearest<F>(&self, center: Vec2, n: usize, filter: F) -> Vec<(EntityId, f32)>
+    where
+        F: Fn(EntityId) -> bool,
+    {
+        let mut found: Vec<(EntityId, f32)> = Vec::new();
+        if n == 0 || self.cells.is_empty() {
+            return found;
+        }
+        
+        let origin = self.cell_of(center);
+        let max_ring = [
+            origin.0 - self.min_cell.0,
+            self.max_cell.0 - origin.0,
+            origin.1 - self.min_cell.1,
+            self.max_cell.1 - origin.1,
+        ].into_iter().max().unwrap_or(0).max(0);
+        
+        for ring in 0..=max_ring {
+            for cell in ring_cells(origin, ring) {
+                for &entity in self.cells.get(&cell).into_iter().flatten() {
+                    if filter(entity) {
+                        found.push((entity, (self.positions[&entity] - center).length()));
+                    }
+                }
+            }
+            
+            // Anything outside this ring is at least `ring` cells away
+            if found.len() >= n {
+                found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                if found[n - 1].1 <= ring as f32 * self.cell_size {
+                    break;
+                }
+            }
+        }
+        
+        found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        found.truncate(n);
+        found
+    }
+    
+    fn cell_of(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+// The square ring of cells exactly `ring` steps from `origin`
+fn ring_cells(origin: (i32, i32), ring: i32) -> Vec<(i32, i32)> {
+    if ring == 0 {
+        return vec![origin];
+    }
+    
+    let mut cells = Vec::with_capacity(8 * ring as usize);
+    for d in -ring..=ring {
+        cells.push((origin.0 + d, origin.1 - ring));
+        cells.push((origin.0 + d, origin.1 + ring));
+    }
+    for d in (-ring + 1)..ring {
+        cells.push((origin.0 - ring, origin.1 + d));
+        cells.push((origin.0 + ring, origin.1 + d));
+    }
+    cells
+}
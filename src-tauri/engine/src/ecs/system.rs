@@ -1,47 +1,231 @@
 // src-tauri/engine/src/ecs/system.rs
-use super::{World, EntityId};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use super::{World, Commands};
 use crate::physics::PhysicsWorld;
 
 pub trait System: Send + Sync {
-    fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, dt: f32);
+    fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, commands: &mut Commands, dt: f32);
     
     // Optional methods for system lifecycle
     fn initialize(&mut self, _world: &mut World) {}
     fn cleanup(&mut self, _world: &mut World) {}
 }
 
+// Systems that only read the world, so a group of them can run side by side.
+// Changes go through `commands`, applied once the whole group has finished
+pub trait ParallelSystem: Send + Sync {
+    fn execute(&mut self, world: &World, physics: &PhysicsWorld, commands: &mut Commands, dt: f32);
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("System ordering forms a cycle: {0}")]
+    Cycle(String),
+    
+    #[error("No system registered as {0}")]
+    UnknownSystem(String),
+}
+
+struct ScheduledSystem {
+    name: Option<String>,
+    // Names of systems this one runs after / before. Names nobody registered
+    // are ignored, so a dependency on a stripped debug system is harmless
+    after: Vec<String>,
+    before: Vec<String>,
+    enabled: bool,
+    system: Box<dyn System>,
+}
+
 pub struct SystemSchedule {
-    systems: Vec<Box<dyn System>>,
-    parallel_systems: Vec<Vec<Box<dyn System>>>,
+    // Kept in execution order once `resolve_order` has run
+    systems: Vec<ScheduledSystem>,
+    order_dirty: bool,
+    // Each parallel system records into its own queue
+    parallel_systems: Vec<Vec<(Box<dyn ParallelSystem>, Commands)>>,
+    commands: Commands,
 }
 
 impl SystemSchedule {
     pub fn new() -> Self {
         Self {
             systems: Vec::new(),
+            order_dirty: false,
             parallel_systems: Vec::new(),
+            commands: Commands::new(),
         }
     }
     
+    // Unnamed systems run in insertion order relative to each other
     pub fn add_system(&mut self, system: Box<dyn System>) {
-        self.systems.push(system);
+        self.insert(None, Vec::new(), Vec::new(), system);
+    }
+    
+    // Named systems can be ordered against with `add_system_after`/`add_system_before`
+    pub fn add_named_system(&mut self, name: &str, system: Box<dyn System>) {
+        self.insert(Some(name), Vec::new(), Vec::new(), system);
+    }
+    
+    pub fn add_system_after(&mut self, name: &str, after: &[&str], system: Box<dyn System>) {
+        self.insert(Some(name), after.iter().map(|s| s.to_string()).collect(), Vec::new(), system);
+    }
+    
+    pub fn add_system_before(&mut self, name: &str, before: &[&str], system: Box<dyn System>) {
+        self.insert(Some(name), Vec::new(), before.iter().map(|s| s.to_string()).collect(), system);
+    }
+    
+    fn insert(&mut self, name: Option<&str>, after: Vec<String>, before: Vec<String>, system: Box<dyn System>) {
+        self.order_dirty |= name.is_some();
+        self.systems.push(ScheduledSystem {
+            name: name.map(str::to_string),
+            after,
+            before,
+            enabled: true,
+            system,
+        });
+    }
+    
+    // Takes a named system out, e.g. to swap in a recompiled one. Systems
+    // ordered against it keep their other constraints
+    pub fn remove_system(&mut self, name: &str) -> Option<Box<dyn System>> {
+        let index = self.systems.iter().position(|s| s.name.as_deref() == Some(name))?;
+        Some(self.systems.remove(index).system)
+    }
+    
+    // Disabled systems keep their place and state but don't run. Returns
+    // false if no system has that name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.systems.iter_mut().find(|s| s.name.as_deref() == Some(name)) {
+            Some(scheduled) => {
+                scheduled.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.systems.iter().find(|s| s.name.as_deref() == Some(name)).map(|s| s.enabled)
+    }
+    
+    // Sorts systems so every ordering constraint holds, otherwise keeping
+    // insertion order. `execute` does this on demand; call it up front to
+    // find cycles at startup instead
+    pub fn resolve_order(&mut self) -> Result<(), ScheduleError> {
+        let count = self.systems.len();
+        let index_of: HashMap<&str, usize> = self.systems.iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.name.as_deref().map(|name| (name, i)))
+            .collect();
+        
+        // edges[i] holds the systems that have to wait for i
+        let mut edges = vec![Vec::new(); count];
+        for (i, scheduled) in self.systems.iter().enumerate() {
+            for dependency in &scheduled.after {
+                if let Some(&j) = index_of.get(dependency.as_str()) {
+                    edges[j].push(i);
+                }
+            }
+            for dependent in &scheduled.before {
+                if let Some(&j) = index_of.get(dependent.as_str()) {
+                    edges[i].push(j);
+                }
+            }
+        }
+        
+        let mut waiting_on = vec![0usize; count];
+        for targets in &edges {
+            for &j in targets {
+                waiting_on[j] += 1;
+            }
+        }
+        
+        // Always take the earliest-inserted ready system so unrelated
+        // systems keep their relative order
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..count)
+            .filter(|&i| waiting_on[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(count);
+        
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(i);
+            for &j in &edges[i] {
+                waiting_on[j] -= 1;
+                if waiting_on[j] == 0 {
+                    ready.push(Reverse(j));
+                }
+            }
+        }
+        
+        if order.len() < count {
+            return Err(ScheduleError::Cycle(self.cycle_path(&edges, &waiting_on)));
+        }
+        
+        let mut slots: Vec<Option<ScheduledSystem>> = self.systems.drain(..).map(Some).collect();
+        self.systems = order.into_iter().filter_map(|i| slots[i].take()).collect();
+        self.order_dirty = false;
+        Ok(())
+    }
+    
+    // Every system left unsorted waits on another unsorted one, so walking
+    // back through those waits must revisit a system
+    fn cycle_path(&self, edges: &[Vec<usize>], waiting_on: &[usize]) -> String {
+        let stuck = |i: usize| waiting_on[i] > 0;
+        let mut walk = vec![(0..edges.len()).find(|&i| stuck(i)).unwrap_or(0)];
+        
+        loop {
+            let current = *walk.last().unwrap();
+            let Some(previous) = (0..edges.len()).find(|&i| stuck(i) && edges[i].contains(&current)) else {
+                break;
+            };
+            
+            if let Some(start) = walk.iter().position(|&i| i == previous) {
+                let mut cycle = walk[start..].to_vec();
+                cycle.push(previous);
+                cycle.reverse();
+                return cycle.iter()
+                    .map(|&i| self.systems[i].name.as_deref().unwrap_or("<unnamed>"))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+            }
+            walk.push(previous);
+        }
+        
+        String::new()
     }
     
-    pub fn add_parallel_systems(&mut self, systems: Vec<Box<dyn System>>) {
-        self.parallel_systems.push(systems);
+    pub fn add_parallel_systems(&mut self, systems: Vec<Box<dyn ParallelSystem>>) {
+        self.parallel_systems.push(systems.into_iter().map(|system| (system, Commands::new())).collect());
     }
     
     pub fn execute(&mut self, world: &mut World, physics: &mut PhysicsWorld, dt: f32) {
+        if self.order_dirty {
+            // Fall back to insertion order rather than stalling the game
+            if let Err(e) = self.resolve_order() {
+                log::error!("{}", e);
+                self.order_dirty = false;
+            }
+        }
+        
         // Execute sequential systems
-        for system in &mut self.systems {
-            system.execute(world, physics, dt);
+        for scheduled in self.systems.iter_mut().filter(|s| s.enabled) {
+            scheduled.system.execute(world, physics, &mut self.commands, dt);
+            self.commands.apply(world);
         }
         
         // Execute parallel system groups
-        // In production, you'd use rayon or similar for actual parallelism
         for group in &mut self.parallel_systems {
-            for system in group {
-                system.execute(world, physics, dt);
+            let (shared_world, shared_physics) = (&*world, &*physics);
+            group.par_iter_mut().for_each(|(system, commands)| {
+                system.execute(shared_world, shared_physics, commands, dt);
+            });
+            
+            // Registration order, so the outcome doesn't depend on which thread finished first
+            for (_, commands) in group.iter_mut() {
+                commands.apply(world);
             }
         }
     }
@@ -50,4 +234,84 @@ impl SystemSchedule {
         self.systems.clear();
         self.parallel_systems.clear();
     }
-}
\ No newline at end of file
+}
+
+// A system as stored in a compiled game: which registered constructor builds
+// it, the numbers it is built with, and the named systems it runs after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemDescriptor {
+    pub name: String,
+    pub params: BTreeMap<String, f32>,
+    pub after: Vec<String>,
+}
+
+impl SystemDescriptor {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: BTreeMap::new(),
+            after: Vec::new(),
+        }
+    }
+    
+    pub fn with_param(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.params.insert(name.into(), value);
+        self
+    }
+    
+    pub fn after(mut self, name: impl Into<String>) -> Self {
+        self.after.push(name.into());
+        self
+    }
+    
+    pub fn param(&self, name: &str, default: f32) -> f32 {
+        self.params.get(name).copied().unwrap_or(default)
+    }
+}
+
+type SystemConstructor = Box<dyn Fn(&SystemDescriptor) -> Box<dyn System> + Send + Sync>;
+
+// Turns descriptors back into systems. Games register a constructor for each
+// system name they ship before loading
+#[derive(Default)]
+pub struct SystemRegistry {
+    constructors: HashMap<String, SystemConstructor>,
+}
+
+impl SystemRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn(&SystemDescriptor) -> Box<dyn System> + Send + Sync + 'static,
+    {
+        self.constructors.insert(name.to_string(), Box::new(constructor));
+    }
+    
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+    
+    pub fn build(&self, descriptor: &SystemDescriptor) -> Result<Box<dyn System>, ScheduleError> {
+        let constructor = self.constructors.get(&descriptor.name)
+            .ok_or_else(|| ScheduleError::UnknownSystem(descriptor.name.clone()))?;
+        Ok(constructor(descriptor))
+    }
+    
+    // Builds every descriptor into `schedule` under its name and resolves
+    // the order. Nothing is added unless all of them build
+    pub fn instantiate(&self, descriptors: &[SystemDescriptor], schedule: &mut SystemSchedule) -> Result<(), ScheduleError> {
+        let systems = descriptors.iter()
+            .map(|descriptor| self.build(descriptor))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        for (descriptor, system) in descriptors.iter().zip(systems) {
+            let after: Vec<&str> = descriptor.after.iter().map(String::as_str).collect();
+            schedule.add_system_after(&descriptor.name, &after, system);
+        }
+        schedule.resolve_order()
+    }
+}
+
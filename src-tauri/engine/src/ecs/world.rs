@@ -1,14 +1,30 @@
 // src-tauri/engine/src/ecs/world.rs
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
-use super::{Component, ComponentStorage, EntityId, Query};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use super::{Component, ComponentStorage, Entity, EntityId, Query, WorldDiff, ComponentChange, SavedScene, SavedEntity, SceneError};
+use super::snapshot::{Inspector, json_approx_eq, short_type_name};
+use crate::physics::PhysicsWorld;
+
+type AddHook = Box<dyn Fn(&World, EntityId, &mut PhysicsWorld) + Send + Sync>;
 
 pub struct World {
     entities: Vec<EntityId>,
     components: ComponentStorage,
     next_entity_id: EntityId,
     entity_generation: HashMap<EntityId, u32>,
+    // Indexed by id, so liveness checks don't scan the free list
+    alive: Vec<bool>,
     free_entities: Vec<EntityId>,
+    destroyed: Vec<Entity>,
+    resources: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    inspectors: Vec<Inspector>,
+    add_hooks: HashMap<TypeId, Vec<AddHook>>,
+    // Inserts of hooked component types waiting for `flush_add_hooks`
+    pending_adds: Vec<(TypeId, EntityId)>,
+    // Most entities alive at once; None is unlimited
+    max_entities: Option<usize>,
 }
 
 impl World {
@@ -22,51 +38,172 @@ impl World {
             components: ComponentStorage::new(),
             next_entity_id: 0,
             entity_generation: HashMap::with_capacity(capacity),
+            alive: Vec::with_capacity(capacity),
             free_entities: Vec::new(),
+            destroyed: Vec::new(),
+            resources: HashMap::new(),
+            inspectors: Vec::new(),
+            add_hooks: HashMap::new(),
+            pending_adds: Vec::new(),
+            max_entities: None,
+        }
+    }
+    
+    pub fn set_max_entities(&mut self, max_entities: Option<usize>) {
+        self.max_entities = max_entities;
+    }
+    
+    pub fn max_entities(&self) -> Option<usize> {
+        self.max_entities
+    }
+    
+    // Creating past `max_entities` is a bug (usually a runaway spawn loop),
+    // so it panics; use `try_create_entity` where running out is expected
+    pub fn create_entity(&mut self) -> Entity {
+        match self.try_create_entity() {
+            Some(entity) => entity,
+            None => panic!("World is full: {} entities alive", self.entities.len()),
         }
     }
     
-    pub fn create_entity(&mut self) -> EntityId {
+    // None once `max_entities` entities are alive
+    pub fn try_create_entity(&mut self) -> Option<Entity> {
+        if self.max_entities.filter(|&max| self.entities.len() >= max).is_some() {
+            return None;
+        }
+        
         if let Some(id) = self.free_entities.pop() {
-            // Reuse entity ID with new generation
-            let gen = self.entity_generation.get(&id).copied().unwrap_or(0) + 1;
-            self.entity_generation.insert(id, gen);
+            // Reuse entity ID; its generation was bumped when it was freed
+            let generation = self.entity_generation.get(&id).copied().unwrap_or(0);
             self.entities.push(id);
-            id
+            self.set_alive(id, true);
+            Some(Entity::new(id, generation))
         } else {
             let id = self.next_entity_id;
             self.next_entity_id += 1;
             self.entities.push(id);
             self.entity_generation.insert(id, 0);
-            id
+            self.set_alive(id, true);
+            Some(Entity::new(id, 0))
         }
     }
     
-    pub fn destroy_entity(&mut self, entity: EntityId) -> bool {
-        if let Some(idx) = self.entities.iter().position(|&e| e == entity) {
+    pub fn destroy_entity(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        
+        if let Some(idx) = self.entities.iter().position(|&e| e == entity.id) {
             self.entities.swap_remove(idx);
-            self.components.remove_all(entity);
-            self.free_entities.push(entity);
+            self.components.remove_all(entity.id);
+            self.free_id(entity.id);
+            self.destroyed.push(entity);
             true
         } else {
             false
         }
     }
     
-    pub fn add_component<T: Component>(&mut self, entity: EntityId, component: T) {
-        self.components.insert(entity, component);
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
+        EntityBuilder {
+            world: self,
+            components: Vec::new(),
+        }
+    }
+    
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.id_alive(entity.id) && self.entity_generation.get(&entity.id) == Some(&entity.generation)
+    }
+    
+    // Upgrades a raw id (from a query or a collision event) to a handle
+    pub fn entity(&self, id: EntityId) -> Option<Entity> {
+        if !self.id_alive(id) {
+            return None;
+        }
+        
+        self.entity_generation
+            .get(&id)
+            .map(|&generation| Entity::new(id, generation))
+    }
+    
+    // Silently replaces a component the entity already has; use
+    // `insert_component` to get the old one back or `try_add_component` to
+    // refuse duplicates. False only if the entity is dead
+    pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        
+        self.components.insert(entity.id, component);
+        self.note_added(TypeId::of::<T>(), entity.id);
+        true
+    }
+    
+    // Adds or replaces, returning the component that was there before. A dead
+    // entity gets nothing and returns None
+    pub fn insert_component<T: Component>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        
+        let previous = self.components.insert(entity.id, component);
+        self.note_added(TypeId::of::<T>(), entity.id);
+        previous
+    }
+    
+    // Adds only if the entity has no `T` yet; false (leaving the existing
+    // component untouched) if it does or the entity is dead
+    pub fn try_add_component<T: Component>(&mut self, entity: Entity, component: T) -> bool {
+        if !self.is_alive(entity) || self.has_component::<T>(entity) {
+            return false;
+        }
+        
+        self.add_component(entity, component)
+    }
+    
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        
+        self.components.remove::<T>(entity.id)
+    }
+    
+    pub fn has_component<T: Component>(&self, entity: Entity) -> bool {
+        self.is_alive(entity) && self.components.contains::<T>(entity.id)
     }
     
-    pub fn remove_component<T: Component>(&mut self, entity: EntityId) -> Option<T> {
-        self.components.remove::<T>(entity)
+    // Every entity holding a `T`, in ascending id order like queries
+    pub fn entities_with<T: Component>(&self) -> impl Iterator<Item = EntityId> {
+        let mut ids = self.components.entities::<T>().to_vec();
+        ids.sort_unstable();
+        ids.into_iter()
     }
     
-    pub fn get_component<T: Component>(&self, entity: EntityId) -> Option<&T> {
-        self.components.get::<T>(entity)
+    pub fn get_component<T: Component>(&self, entity: Entity) -> Option<&T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        
+        self.components.get::<T>(entity.id)
     }
     
-    pub fn get_component_mut<T: Component>(&mut self, entity: EntityId) -> Option<&mut T> {
-        self.components.get_mut::<T>(entity)
+    pub fn get_component_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        
+        self.components.get_mut::<T>(entity.id)
+    }
+    
+    // Strips every component but keeps the entity (and its handle) alive
+    pub fn remove_all_components(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        
+        self.components.remove_all(entity.id);
+        true
     }
     
     pub fn query<Q: Query>(&self) -> Q::Iter<'_> {
@@ -77,13 +214,332 @@ impl World {
         Q::query_mut(&mut self.components)
     }
     
+    // Entities whose `T` was inserted or mutably borrowed since the last
+    // `clear_change_ticks`. Flags accumulate across every system in a tick, so
+    // reading after `SystemSchedule::execute` sees the whole tick's writes;
+    // clear once the changes have been consumed (e.g. synced to the frontend)
+    pub fn changed<T: Component>(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        self.components
+            .get_storage::<T>()
+            .into_iter()
+            .flat_map(|storage| storage.changed())
+    }
+    
+    pub fn clear_change_ticks(&mut self) {
+        self.components.clear_changed();
+    }
+    
+    // Runs `hook` for every `T` inserted from now on, however it got there.
+    // The world doesn't own the physics world, so hooks fire when the owner
+    // calls `flush_add_hooks`
+    pub fn on_add<T, F>(&mut self, hook: F)
+    where
+        T: Component,
+        F: Fn(EntityId, &T, &mut PhysicsWorld) + Send + Sync + 'static,
+    {
+        self.add_hooks.entry(TypeId::of::<T>()).or_default().push(Box::new(move |world, id, physics| {
+            if let Some(component) = world.components.get::<T>(id) {
+                hook(id, component, physics);
+            }
+        }));
+    }
+    
+    pub fn flush_add_hooks(&mut self, physics: &mut PhysicsWorld) {
+        for (type_id, id) in std::mem::take(&mut self.pending_adds) {
+            if let Some(hooks) = self.add_hooks.get(&type_id) {
+                for hook in hooks {
+                    hook(self, id, physics);
+                }
+            }
+        }
+    }
+    
+    fn note_added(&mut self, type_id: TypeId, id: EntityId) {
+        if self.add_hooks.contains_key(&type_id) {
+            self.pending_adds.push((type_id, id));
+        }
+    }
+    
+    pub fn insert_resource<R: Send + Sync + 'static>(&mut self, resource: R) {
+        self.resources.insert(TypeId::of::<R>(), Box::new(resource));
+    }
+    
+    pub fn remove_resource<R: Send + Sync + 'static>(&mut self) -> Option<R> {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .and_then(|r| r.downcast::<R>().ok())
+            .map(|r| *r)
+    }
+    
+    pub fn resource<R: Send + Sync + 'static>(&self) -> Option<&R> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .and_then(|r| r.downcast_ref::<R>())
+    }
+    
+    pub fn resource_mut<R: Send + Sync + 'static>(&mut self) -> Option<&mut R> {
+        self.resources
+            .get_mut(&TypeId::of::<R>())
+            .and_then(|r| r.downcast_mut::<R>())
+    }
+    
+    pub fn has_resource<R: Send + Sync + 'static>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+    
+    // Opts a component type into `inspect`/`diff`/`approx_eq`
+    pub fn register_inspectable<T: Component + Serialize>(&mut self) {
+        if !self.inspectors.iter().any(|i| i.type_id == TypeId::of::<T>()) {
+            self.inspectors.push(Inspector::of::<T>());
+        }
+    }
+    
+    // Also makes the type part of saved scenes, under its short type name
+    pub fn register_serializable<T: Component + Serialize + DeserializeOwned>(&mut self) -> Result<(), SceneError> {
+        self.register_serializable_as::<T>(short_type_name::<T>())
+    }
+    
+    // Saves the type under `name`, for types whose short names collide or that
+    // should keep loading old scenes after a rename
+    pub fn register_serializable_as<T: Component + Serialize + DeserializeOwned>(&mut self, name: &'static str) -> Result<(), SceneError> {
+        let taken = self.inspectors
+            .iter()
+            .any(|i| i.name == name && i.from_json.is_some() && i.type_id != TypeId::of::<T>());
+        if taken {
+            return Err(SceneError::DuplicateComponentName(name.to_string()));
+        }
+        
+        self.inspectors.retain(|i| i.type_id != TypeId::of::<T>());
+        self.inspectors.push(Inspector { name, ..Inspector::serializable::<T>() });
+        Ok(())
+    }
+    
+    // Entities come out in id order, components in registration order
+    pub fn serialize_scene(&self) -> SavedScene {
+        let ids: BTreeSet<EntityId> = self.entities.iter().copied().collect();
+        
+        let entities = ids.into_iter()
+            .map(|id| SavedEntity {
+                id,
+                generation: self.entity_generation.get(&id).copied().unwrap_or(0),
+                components: self.inspectors.iter()
+                    .filter(|i| i.from_json.is_some())
+                    .filter_map(|i| (i.to_json)(&self.components, id).map(|json| (i.name.to_string(), json)))
+                    .collect(),
+            })
+            .collect();
+        
+        SavedScene { entities }
+    }
+    
+    // Replaces every entity with the scene's. The scene is checked before
+    // anything is cleared, so a bad scene leaves the world untouched
+    pub fn deserialize_scene(&mut self, scene: &SavedScene) -> Result<(), SceneError> {
+        if let Some(max) = self.max_entities.filter(|&max| scene.entities.len() > max) {
+            return Err(SceneError::TooManyEntities { count: scene.entities.len(), max });
+        }
+        
+        let mut seen = BTreeSet::new();
+        let mut loads = Vec::new();
+        
+        for saved in &scene.entities {
+            if !seen.insert(saved.id) {
+                return Err(SceneError::DuplicateEntity(saved.id));
+            }
+            
+            for (name, json) in &saved.components {
+                let inspector = self.inspectors.iter()
+                    .find(|i| i.name == name && i.from_json.is_some())
+                    .ok_or_else(|| SceneError::UnknownComponent { entity: saved.id, component: name.clone() })?;
+                loads.push((saved.id, *inspector, json.clone()));
+            }
+        }
+        
+        // Decoded into fresh storage that becomes the world's once all of it succeeded
+        let mut staged = ComponentStorage::new();
+        for (id, inspector, json) in &loads {
+            let from_json = inspector.from_json.expect("only serializable inspectors are loaded");
+            from_json(&mut staged, *id, json.clone()).map_err(|e| SceneError::InvalidComponent {
+                entity: *id,
+                component: inspector.name.to_string(),
+                message: e.to_string(),
+            })?;
+        }
+        
+        self.clear();
+        self.components = staged;
+        
+        for saved in &scene.entities {
+            self.entity_generation.insert(saved.id, saved.generation);
+            self.entities.push(saved.id);
+            self.set_alive(saved.id, true);
+            self.next_entity_id = self.next_entity_id.max(saved.id + 1);
+        }
+        
+        // Every id below the high mark the scene doesn't use is free again,
+        // lowest handed out first
+        self.free_entities = (0..self.next_entity_id).rev().filter(|id| !seen.contains(id)).collect();
+        
+        for (id, inspector, _) in loads {
+            self.note_added(inspector.type_id, id);
+        }
+        
+        Ok(())
+    }
+    
+    pub fn inspect(&self, entity: EntityId) -> BTreeMap<&'static str, Value> {
+        self.inspectors
+            .iter()
+            .filter_map(|i| (i.to_json)(&self.components, entity).map(|json| (i.name, json)))
+            .collect()
+    }
+    
+    // Compares live entities by id and their registered components by JSON
+    pub fn diff(&self, other: &World) -> WorldDiff {
+        self.diff_with(other, |a, b| a == b)
+    }
+    
+    // Like `diff(other).is_empty()`, but floats may differ by up to `epsilon`
+    pub fn approx_eq(&self, other: &World, epsilon: f32) -> bool {
+        self.diff_with(other, |a, b| json_approx_eq(a, b, epsilon as f64)).is_empty()
+    }
+    
+    fn diff_with(&self, other: &World, same: impl Fn(&Value, &Value) -> bool) -> WorldDiff {
+        let ours: BTreeSet<EntityId> = self.entities.iter().copied().collect();
+        let theirs: BTreeSet<EntityId> = other.entities.iter().copied().collect();
+        
+        let mut diff = WorldDiff {
+            added: theirs.difference(&ours).copied().collect(),
+            removed: ours.difference(&theirs).copied().collect(),
+            changed: Vec::new(),
+        };
+        
+        for &entity in ours.intersection(&theirs) {
+            let before = self.inspect(entity);
+            let after = other.inspect(entity);
+            let names: BTreeSet<&'static str> = before.keys().chain(after.keys()).copied().collect();
+            
+            for name in names {
+                let (b, a) = (before.get(name), after.get(name));
+                let unchanged = match (b, a) {
+                    (Some(b), Some(a)) => same(b, a),
+                    (None, None) => true,
+                    _ => false,
+                };
+                
+                if !unchanged {
+                    diff.changed.push(ComponentChange {
+                        entity,
+                        component: name,
+                        before: b.cloned(),
+                        after: a.cloned(),
+                    });
+                }
+            }
+        }
+        
+        diff
+    }
+    
+    // Digest of live entities and their registered components. Equal worlds
+    // hash equally regardless of insertion order, so replays can checkpoint it
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = blake3::Hasher::new();
+        let ids: BTreeSet<EntityId> = self.entities.iter().copied().collect();
+        
+        for id in ids {
+            hasher.update(&id.to_le_bytes());
+            for (name, json) in self.inspect(id) {
+                hasher.update(name.as_bytes());
+                hasher.update(json.to_string().as_bytes());
+            }
+        }
+        
+        let digest = hasher.finalize();
+        u64::from_le_bytes(digest.as_bytes()[..8].try_into().unwrap())
+    }
+    
     pub fn entity_count(&self) -> usize {
         self.entities.len()
     }
     
     pub fn clear(&mut self) {
-        self.entities.clear();
+        let ids: Vec<EntityId> = self.entities.drain(..).collect();
+        for id in ids {
+            let generation = self.entity_generation.get(&id).copied().unwrap_or(0);
+            self.free_id(id);
+            self.destroyed.push(Entity::new(id, generation));
+        }
         self.components.clear();
-        self.free_entities.clear();
     }
-}
\ No newline at end of file
+    
+    // Entities destroyed since the last call, as the handles they had while
+    // alive; the engine forwards these to the physics world, which keeps its
+    // own body/collider maps. An id may be live again under a newer generation
+    pub fn take_destroyed(&mut self) -> Vec<Entity> {
+        std::mem::take(&mut self.destroyed)
+    }
+    
+    fn free_id(&mut self, id: EntityId) {
+        // Bump the generation so outstanding handles to this slot go stale
+        *self.entity_generation.entry(id).or_insert(0) += 1;
+        self.set_alive(id, false);
+        self.free_entities.push(id);
+    }
+    
+    fn id_alive(&self, id: EntityId) -> bool {
+        self.alive.get(id as usize).copied().unwrap_or(false)
+    }
+    
+    fn set_alive(&mut self, id: EntityId, alive: bool) {
+        let slot = id as usize;
+        if slot >= self.alive.len() {
+            self.alive.resize(slot + 1, false);
+        }
+        self.alive[slot] = alive;
+    }
+}
+
+type ComponentInsert = (TypeId, Box<dyn FnOnce(&mut ComponentStorage, EntityId)>);
+
+// Collects components and only allocates the entity on `build`, then inserts
+// everything in a single pass over the storages
+pub struct EntityBuilder<'w> {
+    world: &'w mut World,
+    components: Vec<ComponentInsert>,
+}
+
+impl<'w> EntityBuilder<'w> {
+    pub fn with<T: Component>(mut self, component: T) -> Self {
+        self.components.push((TypeId::of::<T>(), Box::new(move |storage: &mut ComponentStorage, entity: EntityId| {
+            storage.insert(entity, component);
+        })));
+        self
+    }
+    
+    pub fn build(self) -> Entity {
+        let entity = self.world.create_entity();
+        self.insert_into(entity)
+    }
+    
+    // Like `build`, but None instead of a panic when the world is full
+    pub fn try_build(self) -> Option<Entity> {
+        let entity = self.world.try_create_entity()?;
+        Some(self.insert_into(entity))
+    }
+    
+    fn insert_into(self, entity: Entity) -> Entity {
+        for (type_id, insert) in self.components {
+            insert(&mut self.world.components, entity.id);
+            self.world.note_added(type_id, entity.id);
+        }
+        
+        entity
+    }
+    
+    // Commits like `build` but hands back the raw id, which is what storages
+    // and the physics world are keyed by
+    pub fn id(self) -> EntityId {
+        self.build().id
+    }
+}
@@ -0,0 +1,16 @@
+// src-tauri/engine/src/input/event.rs
+use serde::{Deserialize, Serialize};
+
+// Raw device events as the frontend forwards them. Keys use
+// `KeyboardEvent.code` names and mouse positions are in screen pixels;
+// `DreamEngine::handle_input_event` maps them into `Input`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputEvent {
+    KeyDown { code: String },
+    KeyUp { code: String },
+    MouseMove { x: f32, y: f32 },
+    MouseDown { button: u8 },
+    MouseUp { button: u8 },
+    Axis { name: String, value: f32 },
+}
@@ -0,0 +1,97 @@
+// src-tauri/engine/src/input/map.rs
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Physical inputs use the browser's `KeyboardEvent.code` names for keys
+// ("Space", "KeyA", "ArrowLeft") so bindings match what the frontend forwards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(String),
+    MouseButton(u8),
+    GamepadButton(u8),
+}
+
+impl InputBinding {
+    pub fn key(code: &str) -> Self {
+        Self::Key(code.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    // Two digital inputs driving -1.0 / +1.0
+    Buttons { negative: InputBinding, positive: InputBinding },
+    // A named analog axis reported by the platform (e.g. "LeftStickX")
+    Analog(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    #[serde(default)]
+    actions: HashMap<String, Vec<InputBinding>>,
+    #[serde(default)]
+    axes: HashMap<String, Vec<AxisBinding>>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+    
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+    
+    pub fn with_action(mut self, action: &str, binding: InputBinding) -> Self {
+        self.bind_action(action, binding);
+        self
+    }
+    
+    pub fn with_axis(mut self, axis: &str, binding: AxisBinding) -> Self {
+        self.bind_axis(axis, binding);
+        self
+    }
+    
+    pub fn bind_action(&mut self, action: &str, binding: InputBinding) {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+    
+    // Replaces every binding of an action, used for runtime remapping
+    pub fn rebind_action(&mut self, action: &str, bindings: Vec<InputBinding>) {
+        self.actions.insert(action.to_string(), bindings);
+    }
+    
+    pub fn unbind_action(&mut self, action: &str) -> Option<Vec<InputBinding>> {
+        self.actions.remove(action)
+    }
+    
+    pub fn action_bindings(&self, action: &str) -> &[InputBinding] {
+        self.actions.get(action).map(|b| b.as_slice()).unwrap_or(&[])
+    }
+    
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        let bindings = self.axes.entry(axis.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+    
+    pub fn rebind_axis(&mut self, axis: &str, bindings: Vec<AxisBinding>) {
+        self.axes.insert(axis.to_string(), bindings);
+    }
+    
+    pub fn unbind_axis(&mut self, axis: &str) -> Option<Vec<AxisBinding>> {
+        self.axes.remove(axis)
+    }
+    
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map(|b| b.as_slice()).unwrap_or(&[])
+    }
+}
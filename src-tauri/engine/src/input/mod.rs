@@ -0,0 +1,8 @@
+// src-tauri/engine/src/input/mod.rs
+mod state;
+mod map;
+mod event;
+
+pub use state::*;
+pub use map::*;
+pub use event::*;
@@ -0,0 +1,161 @@
+// src-tauri/engine/src/input/state.rs
+use super::{AxisBinding, InputBinding, InputMap};
+use crate::math::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Everything a fixed step can read from `Input`, in a stable order so it can
+// be recorded and fed back in
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputSnapshot {
+    pub pressed: Vec<InputBinding>,
+    pub analog_axes: BTreeMap<String, f32>,
+    #[serde(default)]
+    pub just_pressed: Vec<InputBinding>,
+    #[serde(default)]
+    pub mouse_position: Vec2,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    pressed: HashSet<InputBinding>,
+    // Pressed since the end of the last fixed step
+    just_pressed: HashSet<InputBinding>,
+    analog_axes: HashMap<String, f32>,
+    // World space
+    mouse_position: Vec2,
+    map: InputMap,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_map(map: InputMap) -> Self {
+        Self {
+            map,
+            ..Default::default()
+        }
+    }
+    
+    pub fn map(&self) -> &InputMap {
+        &self.map
+    }
+    
+    pub fn map_mut(&mut self) -> &mut InputMap {
+        &mut self.map
+    }
+    
+    pub fn set_map(&mut self, map: InputMap) {
+        self.map = map;
+    }
+    
+    // Holding a key down repeats the press; only the first one counts as
+    // just pressed
+    pub fn press(&mut self, binding: InputBinding) {
+        if !self.pressed.contains(&binding) {
+            self.just_pressed.insert(binding.clone());
+        }
+        self.pressed.insert(binding);
+    }
+    
+    pub fn release(&mut self, binding: &InputBinding) {
+        self.pressed.remove(binding);
+    }
+    
+    pub fn is_pressed(&self, binding: &InputBinding) -> bool {
+        self.pressed.contains(binding)
+    }
+    
+    // True for one fixed step after the press, even if the binding was
+    // released again before that step ran
+    pub fn is_just_pressed(&self, binding: &InputBinding) -> bool {
+        self.just_pressed.contains(binding)
+    }
+    
+    pub fn set_mouse_position(&mut self, position: Vec2) {
+        self.mouse_position = position;
+    }
+    
+    pub fn mouse_position(&self) -> Vec2 {
+        self.mouse_position
+    }
+    
+    // Called by the engine once each fixed step has run
+    pub fn end_step(&mut self) {
+        self.just_pressed.clear();
+    }
+    
+    pub fn set_analog_axis(&mut self, name: &str, value: f32) {
+        self.analog_axes.insert(name.to_string(), value.clamp(-1.0, 1.0));
+    }
+    
+    pub fn analog_axis(&self, name: &str) -> f32 {
+        self.analog_axes.get(name).copied().unwrap_or(0.0)
+    }
+    
+    pub fn action_pressed(&self, action: &str) -> bool {
+        self.map.action_bindings(action)
+            .iter()
+            .any(|binding| self.is_pressed(binding))
+    }
+    
+    pub fn action_just_pressed(&self, action: &str) -> bool {
+        self.map.action_bindings(action)
+            .iter()
+            .any(|binding| self.is_just_pressed(binding))
+    }
+    
+    // Sums every binding of the axis so keyboard and gamepad can be held together,
+    // clamped back into -1.0..=1.0
+    pub fn action_axis(&self, axis: &str) -> f32 {
+        let value: f32 = self.map.axis_bindings(axis)
+            .iter()
+            .map(|binding| match binding {
+                AxisBinding::Buttons { negative, positive } => {
+                    let mut v = 0.0;
+                    if self.is_pressed(negative) {
+                        v -= 1.0;
+                    }
+                    if self.is_pressed(positive) {
+                        v += 1.0;
+                    }
+                    v
+                }
+                AxisBinding::Analog(name) => self.analog_axis(name),
+            })
+            .sum();
+        
+        value.clamp(-1.0, 1.0)
+    }
+    
+    pub fn snapshot(&self) -> InputSnapshot {
+        let mut pressed: Vec<InputBinding> = self.pressed.iter().cloned().collect();
+        pressed.sort();
+        let mut just_pressed: Vec<InputBinding> = self.just_pressed.iter().cloned().collect();
+        just_pressed.sort();
+        
+        InputSnapshot {
+            pressed,
+            analog_axes: self.analog_axes.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            just_pressed,
+            mouse_position: self.mouse_position,
+        }
+    }
+    
+    // Replaces the device state wholesale; the input map is left alone
+    pub fn restore(&mut self, snapshot: &InputSnapshot) {
+        self.pressed = snapshot.pressed.iter().cloned().collect();
+        self.analog_axes = snapshot.analog_axes.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        self.just_pressed = snapshot.just_pressed.iter().cloned().collect();
+        self.mouse_position = snapshot.mouse_position;
+    }
+    
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+        self.just_pressed.clear();
+        self.analog_axes.clear();
+        self.mouse_position = Vec2::ZERO;
+    }
+}
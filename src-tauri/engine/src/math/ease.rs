@@ -0,0 +1,69 @@
+// src-tauri/engine/src/math/ease.rs
+use std::f32::consts::PI;
+
+// Easing curves mapping progress `t` in [0, 1] to eased progress; every
+// curve starts at 0 and ends at 1. Input outside the range is clamped. Feed
+// the result to `lerp` to ease a tween or a camera follow
+
+fn unit(t: f32) -> f32 {
+    t.clamp(0.0, 1.0)
+}
+
+pub fn linear(t: f32) -> f32 {
+    unit(t)
+}
+
+pub fn in_quad(t: f32) -> f32 {
+    let t = unit(t);
+    t * t
+}
+
+pub fn out_quad(t: f32) -> f32 {
+    let t = unit(t);
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+pub fn in_out_quad(t: f32) -> f32 {
+    let t = unit(t);
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) * 0.5
+    }
+}
+
+pub fn in_cubic(t: f32) -> f32 {
+    unit(t).powi(3)
+}
+
+pub fn out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - unit(t)).powi(3)
+}
+
+pub fn in_out_cubic(t: f32) -> f32 {
+    let t = unit(t);
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) * 0.5
+    }
+}
+
+pub fn in_sine(t: f32) -> f32 {
+    1.0 - (unit(t) * PI * 0.5).cos()
+}
+
+pub fn out_sine(t: f32) -> f32 {
+    (unit(t) * PI * 0.5).sin()
+}
+
+pub fn in_out_sine(t: f32) -> f32 {
+    (1.0 - (unit(t) * PI).cos()) * 0.5
+}
+
+// Overshoots past 1 before settling back
+pub fn out_back(t: f32) -> f32 {
+    const OVERSHOOT: f32 = 1.70158;
+    let t = unit(t) - 1.0;
+    1.0 + (OVERSHOOT + 1.0) * t.powi(3) + OVERSHOOT * t.powi(2)
+}
@@ -0,0 +1,94 @@
+// src-tauri/engine/src/math/matrix.rs
+use super::{Vec3, Quat};
+use serde::{Deserialize, Serialize};
+
+// Column-major, matching what WGPU expects in uniform buffers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mat4 {
+    pub cols: [[f32; 4]; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self {
+        cols: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+    
+    pub fn from_cols(cols: [[f32; 4]; 4]) -> Self {
+        Self { cols }
+    }
+    
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self::from_scale_rotation_translation(Vec3::ONE, Quat::IDENTITY, translation)
+    }
+    
+    // Equivalent to T * R * S: scale first, then rotate, then translate
+    pub fn from_scale_rotation_translation(scale: Vec3, rotation: Quat, translation: Vec3) -> Self {
+        let q = rotation.normalize();
+        let (x2, y2, z2) = (q.x + q.x, q.y + q.y, q.z + q.z);
+        let (xx, xy, xz) = (q.x * x2, q.x * y2, q.x * z2);
+        let (yy, yz, zz) = (q.y * y2, q.y * z2, q.z * z2);
+        let (wx, wy, wz) = (q.w * x2, q.w * y2, q.w * z2);
+        
+        Self {
+            cols: [
+                [(1.0 - (yy + zz)) * scale.x, (xy + wz) * scale.x, (xz - wy) * scale.x, 0.0],
+                [(xy - wz) * scale.y, (1.0 - (xx + zz)) * scale.y, (yz + wx) * scale.y, 0.0],
+                [(xz + wy) * scale.z, (yz - wx) * scale.z, (1.0 - (xx + yy)) * scale.z, 0.0],
+                [translation.x, translation.y, translation.z, 1.0],
+            ],
+        }
+    }
+    
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let c = &self.cols;
+        let x = c[0][0] * point.x + c[1][0] * point.y + c[2][0] * point.z + c[3][0];
+        let y = c[0][1] * point.x + c[1][1] * point.y + c[2][1] * point.z + c[3][1];
+        let z = c[0][2] * point.x + c[1][2] * point.y + c[2][2] * point.z + c[3][2];
+        let w = c[0][3] * point.x + c[1][3] * point.y + c[2][3] * point.z + c[3][3];
+        
+        if w != 0.0 && w != 1.0 {
+            Vec3::new(x / w, y / w, z / w)
+        } else {
+            Vec3::new(x, y, z)
+        }
+    }
+    
+    // Ignores translation
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        let c = &self.cols;
+        Vec3::new(
+            c[0][0] * vector.x + c[1][0] * vector.y + c[2][0] * vector.z,
+            c[0][1] * vector.x + c[1][1] * vector.y + c[2][1] * vector.z,
+            c[0][2] * vector.x + c[1][2] * vector.y + c[2][2] * vector.z,
+        )
+    }
+    
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(self.cols[3][0], self.cols[3][1], self.cols[3][2])
+    }
+}
+
+impl std::ops::Mul for Mat4 {
+    type Output = Self;
+    
+    fn mul(self, other: Self) -> Self {
+        let mut cols = [[0.0; 4]; 4];
+        for (col, out) in cols.iter_mut().enumerate() {
+            for (row, value) in out.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.cols[k][row] * other.cols[col][k]).sum();
+            }
+        }
+        Self { cols }
+    }
+}
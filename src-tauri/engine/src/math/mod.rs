@@ -2,10 +2,13 @@
 mod vectors;
 mod quaternion;
 mod transform;
+mod matrix;
+pub mod ease;
 
 pub use vectors::*;
 pub use quaternion::*;
 pub use transform::*;
+pub use matrix::*;
 
 // Common math utilities
 pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
@@ -19,4 +22,10 @@ pub fn clamp(value: f32, min: f32, max: f32) -> f32 {
 pub fn remap(value: f32, from_min: f32, from_max: f32, to_min: f32, to_max: f32) -> f32 {
     let normalized = (value - from_min) / (from_max - from_min);
     lerp(to_min, to_max, normalized)
-}
\ No newline at end of file
+}
+
+// Hermite ease from 0 at `edge0` to 1 at `edge1`, flat at both ends
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = clamp((x - edge0) / (edge1 - edge0), 0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
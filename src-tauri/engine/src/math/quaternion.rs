@@ -46,6 +46,32 @@ impl Quat {
         }
     }
     
+    // The rotation taking the unit X, Y and Z axes to the given orthonormal
+    // axes, i.e. the rotation matrix with them as columns
+    pub fn from_rotation_axes(x_axis: Vec3, y_axis: Vec3, z_axis: Vec3) -> Self {
+        let (m00, m10, m20) = (x_axis.x, x_axis.y, x_axis.z);
+        let (m01, m11, m21) = (y_axis.x, y_axis.y, y_axis.z);
+        let (m02, m12, m22) = (z_axis.x, z_axis.y, z_axis.z);
+        
+        // Divide by the largest of w, x, y, z to stay accurate near 180°
+        let trace = m00 + m11 + m22;
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new((m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25 * s)
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            Self::new(0.25 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s)
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            Self::new((m01 + m10) / s, 0.25 * s, (m12 + m21) / s, (m02 - m20) / s)
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            Self::new((m02 + m20) / s, (m12 + m21) / s, 0.25 * s, (m10 - m01) / s)
+        };
+        
+        q.normalize()
+    }
+    
     pub fn from_euler(pitch: f32, yaw: f32, roll: f32) -> Self {
         let half_pitch = pitch * 0.5;
         let half_yaw = yaw * 0.5;
@@ -66,6 +92,49 @@ impl Quat {
         }
     }
     
+    // Inverse of `from_euler`, returning (pitch, yaw, roll). At ±90° pitch roll
+    // and yaw describe the same axis, so roll is pinned to 0 and yaw takes it all
+    pub fn to_euler(self) -> (f32, f32, f32) {
+        let q = self.normalize();
+        let sin_pitch = (2.0 * (q.w * q.y - q.x * q.z)).clamp(-1.0, 1.0);
+        let pitch = sin_pitch.asin();
+        
+        if sin_pitch.abs() > 0.9999 {
+            let yaw = -2.0 * sin_pitch.signum() * q.x.atan2(q.w);
+            return (pitch, yaw, 0.0);
+        }
+        
+        let roll = (2.0 * (q.w * q.x + q.y * q.z)).atan2(1.0 - 2.0 * (q.x * q.x + q.y * q.y));
+        let yaw = (2.0 * (q.w * q.z + q.x * q.y)).atan2(1.0 - 2.0 * (q.y * q.y + q.z * q.z));
+        
+        (pitch, yaw, roll)
+    }
+    
+    // Where the rotation takes the unit X and Y axes: the first two columns
+    // of its rotation matrix
+    pub fn x_axis(self) -> Vec3 {
+        Vec3::new(
+            1.0 - 2.0 * (self.y * self.y + self.z * self.z),
+            2.0 * (self.x * self.y + self.w * self.z),
+            2.0 * (self.x * self.z - self.w * self.y),
+        )
+    }
+    
+    pub fn y_axis(self) -> Vec3 {
+        Vec3::new(
+            2.0 * (self.x * self.y - self.w * self.z),
+            1.0 - 2.0 * (self.x * self.x + self.z * self.z),
+            2.0 * (self.y * self.z + self.w * self.x),
+        )
+    }
+    
+    // The rotation about Z as seen from above, in -π..=π: the heading of the
+    // rotated X axis. Tilts out of the XY plane are flattened away
+    pub fn to_angle_2d(self) -> f32 {
+        let x_axis = self.x_axis();
+        x_axis.y.atan2(x_axis.x)
+    }
+    
     pub fn dot(self, other: Self) -> f32 {
         self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
     }
@@ -1,5 +1,5 @@
 // src-tauri/engine/src/math/transform.rs
-use super::{Vec3, Quat};
+use super::{Vec3, Quat, Mat4};
 use serde::{Deserialize, Serialize};
 use crate::ecs::Component;
 
@@ -42,17 +42,62 @@ impl Transform {
         }
     }
     
+    // Turns `forward()` towards `target`, keeping `up()` as close to `up` as
+    // it can. Looking at its own position leaves the rotation alone, and an
+    // `up` parallel to the view direction is swapped for another axis
     pub fn look_at(mut self, target: Vec3, up: Vec3) -> Self {
         let forward = (target - self.position).normalize();
-        let right = up.cross(forward).normalize();
-        let up = forward.cross(right);
+        if forward == Vec3::ZERO {
+            return self;
+        }
+        
+        let mut right = forward.cross(up).normalize();
+        if right == Vec3::ZERO {
+            let fallback = if forward.x.abs() < 0.9 { Vec3::RIGHT } else { Vec3::UP };
+            right = forward.cross(fallback).normalize();
+        }
+        let up = right.cross(forward);
         
-        // Convert basis vectors to quaternion
-        // This is a simplified version - in production you'd use a proper matrix to quaternion conversion
-        self.rotation = Quat::IDENTITY; // TODO: Implement proper look_at quaternion
+        // Local -Z is forward, so +Z maps to the opposite of it
+        self.rotation = Quat::from_rotation_axes(right, up, -forward);
         self
     }
     
+    // `t` = 0 gives self, 1 gives `to`
+    pub fn interpolate(&self, to: &Transform, t: f32) -> Transform {
+        Self {
+            position: self.position.lerp(to.position, t),
+            rotation: self.rotation.slerp(to.rotation, t),
+            scale: self.scale.lerp(to.scale, t),
+        }
+    }
+    
+    pub fn to_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position)
+    }
+    
+    // Applies `child` first, then self: the world pose of something placed at
+    // `child` relative to this transform
+    pub fn mul_transform(&self, child: &Transform) -> Transform {
+        Self {
+            position: self.transform_point(child.position),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+    
+    // Exact for uniform scale, which is all the hierarchy relies on
+    pub fn inverse(&self) -> Transform {
+        let rotation = self.rotation.inverse();
+        let scale = Vec3::new(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+        
+        Self {
+            position: rotation.rotate_vec3(-self.position) * scale,
+            rotation,
+            scale,
+        }
+    }
+    
     pub fn transform_point(&self, point: Vec3) -> Vec3 {
         self.position + self.rotation.rotate_vec3(point * self.scale)
     }
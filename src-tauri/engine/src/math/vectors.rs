@@ -53,6 +53,22 @@ impl Vec2 {
         self + (other - self) * t
     }
     
+    // Same direction, at most `max` long
+    pub fn clamp_length(self, max: f32) -> Self {
+        let length_squared = self.length_squared();
+        if length_squared > max * max {
+            self * (max / length_squared.sqrt())
+        } else {
+            self
+        }
+    }
+    
+    // Mirrors self across the surface with unit `normal`, e.g. a velocity
+    // bouncing off a wall
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+    
     pub fn rotate(self, angle: f32) -> Self {
         let cos = angle.cos();
         let sin = angle.sin();
@@ -65,6 +81,24 @@ impl Vec2 {
     pub fn angle(self) -> f32 {
         self.y.atan2(self.x)
     }
+    
+    // z component of the 3D cross product
+    pub fn cross(self, other: Self) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+    
+    // Rotated 90 degrees counter-clockwise
+    pub fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+    
+    pub fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+    
+    pub fn to_vec3(self) -> Vec3 {
+        self.extend(0.0)
+    }
 }
 
 impl Add for Vec2 {
@@ -88,6 +122,14 @@ impl Mul<f32> for Vec2 {
     }
 }
 
+// Componentwise, for applying non-uniform scale
+impl Mul for Vec2 {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self { x: self.x * other.x, y: self.y * other.y }
+    }
+}
+
 impl Div<f32> for Vec2 {
     type Output = Self;
     fn div(self, scalar: f32) -> Self {
@@ -184,9 +226,25 @@ impl Vec3 {
         }
     }
     
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+    
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+    
     pub fn xy(self) -> Vec2 {
         Vec2::new(self.x, self.y)
     }
+    
+    pub fn truncate(self) -> Vec2 {
+        self.xy()
+    }
+    
+    pub fn with_z(self, z: f32) -> Self {
+        Self { z, ..self }
+    }
 }
 
 impl Add for Vec3 {
@@ -210,6 +268,14 @@ impl Mul<f32> for Vec3 {
     }
 }
 
+// Componentwise, for applying non-uniform scale
+impl Mul for Vec3 {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self { x: self.x * other.x, y: self.y * other.y, z: self.z * other.z }
+    }
+}
+
 impl Div<f32> for Vec3 {
     type Output = Self;
     fn div(self, scalar: f32) -> Self {
@@ -222,4 +288,149 @@ impl Neg for Vec3 {
     fn neg(self) -> Self {
         Self { x: -self.x, y: -self.y, z: -self.z }
     }
-}
\ No newline at end of file
+}
+// Also used for RGBA colors, with x/y/z/w as r/g/b/a
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vec4 {
+    pub const ZERO: Self = Self { x: 0.0, y: 0.0, z: 0.0, w: 0.0 };
+    pub const ONE: Self = Self { x: 1.0, y: 1.0, z: 1.0, w: 1.0 };
+    pub const WHITE: Self = Self::ONE;
+    pub const BLACK: Self = Self { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+    pub const TRANSPARENT: Self = Self::ZERO;
+    
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+    
+    pub fn splat(v: f32) -> Self {
+        Self { x: v, y: v, z: v, w: v }
+    }
+    
+    pub fn from_array(a: [f32; 4]) -> Self {
+        Self { x: a[0], y: a[1], z: a[2], w: a[3] }
+    }
+    
+    pub fn to_array(self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+    
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+    
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+    
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+    
+    pub fn normalize(self) -> Self {
+        let len = self.length();
+        if len > 0.0 {
+            self / len
+        } else {
+            Self::ZERO
+        }
+    }
+    
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+    
+    pub fn xyz(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+    
+    // Same color with a different alpha, for fades
+    pub fn with_w(self, w: f32) -> Self {
+        Self { w, ..self }
+    }
+}
+
+impl From<[f32; 4]> for Vec4 {
+    fn from(a: [f32; 4]) -> Self {
+        Self::from_array(a)
+    }
+}
+
+impl From<Vec4> for [f32; 4] {
+    fn from(v: Vec4) -> Self {
+        v.to_array()
+    }
+}
+
+impl Add for Vec4 {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self { x: self.x + other.x, y: self.y + other.y, z: self.z + other.z, w: self.w + other.w }
+    }
+}
+
+impl Sub for Vec4 {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self { x: self.x - other.x, y: self.y - other.y, z: self.z - other.z, w: self.w - other.w }
+    }
+}
+
+impl Mul<f32> for Vec4 {
+    type Output = Self;
+    fn mul(self, scalar: f32) -> Self {
+        Self { x: self.x * scalar, y: self.y * scalar, z: self.z * scalar, w: self.w * scalar }
+    }
+}
+
+// Component-wise, i.e. color modulation (tinting)
+impl Mul for Vec4 {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self { x: self.x * other.x, y: self.y * other.y, z: self.z * other.z, w: self.w * other.w }
+    }
+}
+
+impl Div<f32> for Vec4 {
+    type Output = Self;
+    fn div(self, scalar: f32) -> Self {
+        Self { x: self.x / scalar, y: self.y / scalar, z: self.z / scalar, w: self.w / scalar }
+    }
+}
+
+impl Neg for Vec4 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self { x: -self.x, y: -self.y, z: -self.z, w: -self.w }
+    }
+}
+
+impl AddAssign for Vec4 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec4 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vec4 {
+    fn mul_assign(&mut self, scalar: f32) {
+        *self = *self * scalar;
+    }
+}
+
+impl DivAssign<f32> for Vec4 {
+    fn div_assign(&mut self, scalar: f32) {
+        *self = *self / scalar;
+    }
+}
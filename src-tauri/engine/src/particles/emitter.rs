@@ -0,0 +1,199 @@
+// src-tauri/engine/src/particles/emitter.rs
+use serde::{Deserialize, Serialize};
+use crate::ecs::{Component, EntityId, GlobalTransform, World};
+use crate::math::{Transform, Vec2};
+use crate::replay::GameRng;
+
+// Spawns particles at its entity's position. Each particle gets a velocity
+// picked uniformly between `velocity_min` and `velocity_max` per axis and
+// fades from `color_start` to `color_end` over its lifetime
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParticleEmitter {
+    // Particles per second
+    pub rate: f32,
+    // Seconds each particle lives
+    pub lifetime: f32,
+    pub velocity_min: Vec2,
+    pub velocity_max: Vec2,
+    pub color_start: [f32; 4],
+    pub color_end: [f32; 4],
+    // Acceleration applied to every particle
+    pub gravity: Vec2,
+    pub size: f32,
+    #[serde(default = "default_emitting")]
+    pub emitting: bool,
+    // Fractional particles owed from earlier steps
+    #[serde(skip)]
+    pub accumulator: f32,
+}
+
+fn default_emitting() -> bool {
+    true
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 10.0,
+            lifetime: 1.0,
+            velocity_min: Vec2::new(-20.0, -20.0),
+            velocity_max: Vec2::new(20.0, 20.0),
+            color_start: [1.0, 1.0, 1.0, 1.0],
+            color_end: [1.0, 1.0, 1.0, 0.0],
+            gravity: Vec2::ZERO,
+            size: 2.0,
+            emitting: true,
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl Component for ParticleEmitter {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub emitter: EntityId,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub gravity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+    pub color_start: [f32; 4],
+    pub color_end: [f32; 4],
+    pub size: f32,
+}
+
+impl Particle {
+    // Blends the start color into the end color as the particle ages
+    pub fn color(&self) -> [f32; 4] {
+        let t = (self.age / self.lifetime).clamp(0.0, 1.0);
+        let mut color = self.color_start;
+        for (channel, end) in color.iter_mut().zip(self.color_end) {
+            *channel += (end - *channel) * t;
+        }
+        color
+    }
+}
+
+// Every live particle in the world, kept as a resource. The buffer is
+// allocated once at `capacity` and never grows: emitters stop spawning while
+// it is full, and dead particles free their slot for the next spawn
+pub struct ParticleBuffer {
+    particles: Vec<Particle>,
+    capacity: usize,
+}
+
+impl ParticleBuffer {
+    pub const DEFAULT_CAPACITY: usize = 4096;
+    
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+    
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+    
+    // False once the buffer is full
+    pub fn spawn(&mut self, particle: Particle) -> bool {
+        if self.particles.len() >= self.capacity {
+            return false;
+        }
+        self.particles.push(particle);
+        true
+    }
+}
+
+impl Default for ParticleBuffer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+// Ages and moves every particle, drops the expired ones, then lets each
+// emitter spawn what it owes for `dt`. Spread comes from the `GameRng`
+// resource so seeded runs emit identically
+pub fn particle_system(world: &mut World, dt: f32) {
+    let Some(mut buffer) = world.remove_resource::<ParticleBuffer>() else {
+        return;
+    };
+    
+    buffer.particles.retain_mut(|particle| {
+        particle.age += dt;
+        particle.velocity += particle.gravity * dt;
+        particle.position += particle.velocity * dt;
+        particle.age < particle.lifetime
+    });
+    
+    let emitters: Vec<(EntityId, Vec2)> = world.query::<(&Transform, &ParticleEmitter)>()
+        .map(|(id, (transform, _))| {
+            let global = world.entity(id).and_then(|e| world.get_component::<GlobalTransform>(e));
+            (id, global.map_or(transform.position, |global| global.0.position).xy())
+        })
+        .collect();
+    
+    for (id, origin) in emitters {
+        let Some(entity) = world.entity(id) else {
+            continue;
+        };
+        let Some(emitter) = world.get_component_mut::<ParticleEmitter>(entity) else {
+            continue;
+        };
+        if !emitter.emitting || emitter.lifetime <= 0.0 {
+            emitter.accumulator = 0.0;
+            continue;
+        }
+        
+        emitter.accumulator += emitter.rate * dt;
+        let count = emitter.accumulator.floor() as usize;
+        // Whatever the full buffer refuses is dropped, not queued for later
+        emitter.accumulator -= count as f32;
+        let emitter = emitter.clone();
+        
+        for _ in 0..count {
+            let Some(rng) = world.resource_mut::<GameRng>() else {
+                break;
+            };
+            let velocity = Vec2::new(
+                rng.range(emitter.velocity_min.x, emitter.velocity_max.x),
+                rng.range(emitter.velocity_min.y, emitter.velocity_max.y),
+            );
+            
+            let spawned = buffer.spawn(Particle {
+                emitter: id,
+                position: origin,
+                velocity,
+                gravity: emitter.gravity,
+                age: 0.0,
+                lifetime: emitter.lifetime,
+                color_start: emitter.color_start,
+                color_end: emitter.color_end,
+                size: emitter.size,
+            });
+            if !spawned {
+                break;
+            }
+        }
+    }
+    
+    world.insert_resource(buffer);
+}
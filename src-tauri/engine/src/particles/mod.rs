@@ -0,0 +1,4 @@
+// src-tauri/engine/src/particles/mod.rs
+mod emitter;
+
+pub use emitter::*;
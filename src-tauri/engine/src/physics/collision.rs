@@ -3,36 +3,130 @@ use crate::math::Vec2;
 use crate::ecs::{EntityId, Component};
 use serde::{Deserialize, Serialize};
 
+// `offset` moves the shape's center away from the body's position, e.g. to
+// put a hitbox at a character's feet. It doesn't rotate with the body
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Collider {
-    Circle { radius: f32 },
-    Box { half_extents: Vec2 },
-    Polygon { vertices: Vec<Vec2> },
+    Circle {
+        radius: f32,
+        #[serde(default)]
+        offset: Vec2,
+    },
+    Box {
+        half_extents: Vec2,
+        #[serde(default)]
+        offset: Vec2,
+    },
+    Polygon {
+        vertices: Vec<Vec2>,
+        #[serde(default)]
+        offset: Vec2,
+    },
 }
 
 impl Component for Collider {}
 
+#[derive(Debug, thiserror::Error)]
+pub enum ColliderError {
+    #[error("Polygon needs at least 3 vertices, got {0}")]
+    TooFewVertices(usize),
+    #[error("Polygon is not convex")]
+    NotConvex,
+}
+
 impl Collider {
     pub fn circle(radius: f32) -> Self {
-        Self::Circle { radius }
+        Self::Circle { radius, offset: Vec2::ZERO }
     }
     
     pub fn box_collider(width: f32, height: f32) -> Self {
         Self::Box {
             half_extents: Vec2::new(width * 0.5, height * 0.5),
+            offset: Vec2::ZERO,
+        }
+    }
+    
+    // The narrow phase treats polygons as convex, so only convex outlines are
+    // accepted here. Either winding works
+    pub fn polygon(vertices: Vec<Vec2>) -> Result<Self, ColliderError> {
+        if vertices.len() < 3 {
+            return Err(ColliderError::TooFewVertices(vertices.len()));
+        }
+        
+        // Every turn goes the same way and they add up to one full turn;
+        // the second check rejects self-intersecting stars
+        let n = vertices.len();
+        let mut winding = 0.0;
+        let mut total_turn = 0.0f32;
+        for i in 0..n {
+            let edge = vertices[(i + 1) % n] - vertices[i];
+            let next = vertices[(i + 2) % n] - vertices[(i + 1) % n];
+            let cross = edge.cross(next);
+            
+            if cross != 0.0 {
+                if winding * cross < 0.0 {
+                    return Err(ColliderError::NotConvex);
+                }
+                winding = cross;
+            }
+            total_turn += cross.atan2(edge.dot(next));
+        }
+        
+        if winding == 0.0 || (total_turn.abs() - std::f32::consts::TAU).abs() > 1e-3 {
+            return Err(ColliderError::NotConvex);
+        }
+        
+        Ok(Self::Polygon { vertices, offset: Vec2::ZERO })
+    }
+    
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        match &mut self {
+            Collider::Circle { offset: o, .. }
+            | Collider::Box { offset: o, .. }
+            | Collider::Polygon { offset: o, .. } => *o = offset,
+        }
+        self
+    }
+    
+    pub fn offset(&self) -> Vec2 {
+        match self {
+            Collider::Circle { offset, .. }
+            | Collider::Box { offset, .. }
+            | Collider::Polygon { offset, .. } => *offset,
+        }
+    }
+    
+    // Where the shape is centered for a body at `position`
+    pub fn center(&self, position: Vec2) -> Vec2 {
+        position + self.offset()
+    }
+    
+    // Corners relative to `position`, for the shapes SAT handles as polygons
+    pub(crate) fn polygon_points(&self, position: Vec2) -> Option<Vec<Vec2>> {
+        let position = self.center(position);
+        match self {
+            Collider::Circle { .. } => None,
+            Collider::Box { half_extents: h, .. } => Some(vec![
+                position + Vec2::new(-h.x, -h.y),
+                position + Vec2::new(h.x, -h.y),
+                position + Vec2::new(h.x, h.y),
+                position + Vec2::new(-h.x, h.y),
+            ]),
+            Collider::Polygon { vertices, .. } => Some(vertices.iter().map(|&v| position + v).collect()),
         }
     }
     
     pub fn get_aabb(&self, position: Vec2) -> (Vec2, Vec2) {
+        let position = self.center(position);
         match self {
-            Collider::Circle { radius } => {
+            Collider::Circle { radius, .. } => {
                 let r = Vec2::splat(*radius);
                 (position - r, position + r)
             }
-            Collider::Box { half_extents } => {
+            Collider::Box { half_extents, .. } => {
                 (position - *half_extents, position + *half_extents)
             }
-            Collider::Polygon { vertices } => {
+            Collider::Polygon { vertices, .. } => {
                 let mut min = Vec2::new(f32::MAX, f32::MAX);
                 let mut max = Vec2::new(f32::MIN, f32::MIN);
                 
@@ -48,6 +142,35 @@ impl Collider {
             }
         }
     }
+    
+    // Whether `point` lies inside this collider placed at `position`. Polygons
+    // use the even-odd rule, so concave outlines work too
+    pub fn contains_point(&self, position: Vec2, point: Vec2) -> bool {
+        let local = point - self.center(position);
+        
+        match self {
+            Collider::Circle { radius, .. } => local.length_squared() <= radius * radius,
+            Collider::Box { half_extents, .. } => {
+                local.x.abs() <= half_extents.x && local.y.abs() <= half_extents.y
+            }
+            Collider::Polygon { vertices, .. } => {
+                let mut inside = false;
+                let mut j = vertices.len().wrapping_sub(1);
+                
+                for (i, a) in vertices.iter().enumerate() {
+                    let b = vertices[j];
+                    if (a.y > local.y) != (b.y > local.y)
+                        && local.x < (b.x - a.x) * (local.y - a.y) / (b.y - a.y) + a.x
+                    {
+                        inside = !inside;
+                    }
+                    j = i;
+                }
+                
+                inside
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,4 +185,12 @@ pub struct CollisionEvent {
     pub entity_a: EntityId,
     pub entity_b: EntityId,
     pub contact: Contact,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone)]
+pub struct RayHit {
+    pub entity: EntityId,
+    pub point: Vec2,
+    pub normal: Vec2,
+    pub distance: f32,
+}
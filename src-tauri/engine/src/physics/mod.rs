@@ -2,7 +2,9 @@
 mod world;
 mod rigid_body;
 mod collision;
+mod spatial_hash;
 
 pub use world::*;
 pub use rigid_body::*;
-pub use collision::*;
\ No newline at end of file
+pub use collision::*;
+pub use spatial_hash::*;
\ No newline at end of file
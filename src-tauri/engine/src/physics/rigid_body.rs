@@ -25,6 +25,9 @@ pub struct RigidBody {
     pub linear_damping: f32,
     pub angular_damping: f32,
     pub body_type: BodyType,
+    // Bitmasks; a pair only collides when each side's layer is in the other's mask
+    pub collision_layer: u32,
+    pub collision_mask: u32,
 }
 
 impl Default for RigidBody {
@@ -43,6 +46,8 @@ impl Default for RigidBody {
             linear_damping: 0.1,
             angular_damping: 0.1,
             body_type: BodyType::Dynamic,
+            collision_layer: u32::MAX,
+            collision_mask: u32::MAX,
         }
     }
 }
@@ -68,6 +73,16 @@ impl RigidBody {
         self
     }
     
+    pub fn with_collision_layer(mut self, layer: u32, mask: u32) -> Self {
+        self.collision_layer = layer;
+        self.collision_mask = mask;
+        self
+    }
+    
+    pub fn can_collide_with(&self, other: &RigidBody) -> bool {
+        self.collision_layer & other.collision_mask != 0 && other.collision_layer & self.collision_mask != 0
+    }
+    
     pub fn apply_force(&mut self, force: Vec2) {
         if self.body_type == BodyType::Dynamic {
             self.force += force;
@@ -0,0 +1,79 @@
+// src-tauri/engine/src/physics/spatial_hash.rs
+use crate::math::Vec2;
+use crate::ecs::EntityId;
+use std::collections::{HashMap, HashSet};
+
+pub const DEFAULT_CELL_SIZE: f32 = 4.0;
+
+// Uniform grid bucketing AABBs by the cells they touch
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<EntityId>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+        }
+    }
+    
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+    
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+    
+    pub fn insert(&mut self, entity: EntityId, min: Vec2, max: Vec2) {
+        let (min_x, min_y) = self.cell_of(min);
+        let (max_x, max_y) = self.cell_of(max);
+        
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.cells.entry((x, y)).or_default().push(entity);
+            }
+        }
+    }
+    
+    // Every pair sharing at least one cell, reported once as (low, high) and
+    // sorted so the solver sees the same order regardless of cell iteration
+    pub fn candidate_pairs(&self) -> Vec<(EntityId, EntityId)> {
+        let mut seen = HashSet::new();
+        let mut pairs = Vec::new();
+        
+        for bucket in self.cells.values() {
+            for i in 0..bucket.len() {
+                for j in (i + 1)..bucket.len() {
+                    let (a, b) = (bucket[i], bucket[j]);
+                    if a == b {
+                        continue;
+                    }
+                    
+                    let pair = (a.min(b), a.max(b));
+                    if seen.insert(pair) {
+                        pairs.push(pair);
+                    }
+                }
+            }
+        }
+        
+        pairs.sort_unstable();
+        pairs
+    }
+    
+    fn cell_of(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
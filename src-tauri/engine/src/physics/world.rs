@@ -1,34 +1,117 @@
 // src-tauri/engine/src/physics/world.rs
 use crate::math::{Vec2, Vec3};
 use crate::ecs::{EntityId, Component};
-use super::{RigidBody, Collider, CollisionEvent};
+use super::{RigidBody, Collider, CollisionEvent, Contact, BodyType, RayHit, SpatialHash};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+pub type CollisionHandler = Box<dyn FnMut(&CollisionEvent, &mut PhysicsWorld) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhysicsConfig {
+    pub gravity: Vec2,
+    // Passes over every contact per step. One is enough for loose objects;
+    // stacks need several before the impulses settle
+    pub solver_iterations: u32,
+    // Fraction of the remaining penetration pushed out on each pass
+    pub baumgarte: f32,
+    // Penetration left alone so resting contacts don't jitter
+    pub slop: f32,
+    // Start each contact from the impulse it ended the last step with, so a
+    // resting stack doesn't have to rebuild its support from nothing
+    #[serde(default)]
+    pub warm_starting: bool,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: Vec2::new(0.0, -9.81),
+            solver_iterations: 4,
+            baumgarte: 0.2,
+            slop: 0.01,
+            warm_starting: true,
+        }
+    }
+}
+
+// Everything a step reads from `PhysicsWorld`, sorted by entity so equal
+// worlds give equal snapshots. Collision handlers aren't data and stay put
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhysicsSnapshot {
+    pub config: PhysicsConfig,
+    pub bodies: Vec<(EntityId, RigidBody)>,
+    pub colliders: Vec<(EntityId, Collider)>,
+    pub sensors: Vec<EntityId>,
+    // Warm-start impulses per touching pair
+    pub contacts: Vec<(EntityId, EntityId, f32)>,
+    pub accumulator: f32,
+}
+
 pub struct PhysicsWorld {
     bodies: HashMap<EntityId, RigidBody>,
     colliders: HashMap<EntityId, Collider>,
+    // Colliders that report overlaps but never push back
+    sensors: HashSet<EntityId>,
     collision_pairs: Vec<(EntityId, EntityId)>,
+    spatial_hash: SpatialHash,
     collision_events: Vec<CollisionEvent>,
-    gravity: Vec2,
+    // Normal impulse each touching pair ended the last step with
+    contact_cache: HashMap<(EntityId, EntityId), f32>,
+    collision_handlers: Vec<CollisionHandler>,
+    // Removals requested while a step is running are applied once it finishes
+    pending_removals: Vec<EntityId>,
+    stepping: bool,
+    config: PhysicsConfig,
     fixed_timestep: f32,
     accumulator: f32,
+    max_substeps: u32,
 }
 
 impl PhysicsWorld {
     pub fn new() -> Self {
+        Self::with_config(PhysicsConfig::default())
+    }
+    
+    pub fn with_config(config: PhysicsConfig) -> Self {
         Self {
             bodies: HashMap::new(),
             colliders: HashMap::new(),
+            sensors: HashSet::new(),
             collision_pairs: Vec::new(),
+            spatial_hash: SpatialHash::default(),
             collision_events: Vec::new(),
-            gravity: Vec2::new(0.0, -9.81),
+            contact_cache: HashMap::new(),
+            collision_handlers: Vec::new(),
+            pending_removals: Vec::new(),
+            stepping: false,
+            config,
             fixed_timestep: 1.0 / 60.0,
             accumulator: 0.0,
+            max_substeps: 5,
         }
     }
     
     pub fn set_gravity(&mut self, gravity: Vec2) {
-        self.gravity = gravity;
+        self.config.gravity = gravity;
+    }
+    
+    pub fn config(&self) -> &PhysicsConfig {
+        &self.config
+    }
+    
+    pub fn set_config(&mut self, config: PhysicsConfig) {
+        self.config = config;
+    }
+    
+    // Should be around the size of a typical collider; much smaller makes big
+    // colliders touch many cells, much larger puts everything in one bucket
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.spatial_hash = SpatialHash::new(cell_size);
+    }
+    
+    pub fn cell_size(&self) -> f32 {
+        self.spatial_hash.cell_size()
     }
     
     pub fn add_rigid_body(&mut self, entity: EntityId, body: RigidBody) {
@@ -39,9 +122,39 @@ impl PhysicsWorld {
         self.colliders.insert(entity, collider);
     }
     
+    pub fn add_sensor(&mut self, entity: EntityId, collider: Collider) {
+        self.colliders.insert(entity, collider);
+        self.sensors.insert(entity);
+    }
+    
+    pub fn set_sensor(&mut self, entity: EntityId, is_sensor: bool) {
+        if is_sensor {
+            self.sensors.insert(entity);
+        } else {
+            self.sensors.remove(&entity);
+        }
+    }
+    
+    pub fn is_sensor(&self, entity: EntityId) -> bool {
+        self.sensors.contains(&entity)
+    }
+    
+    // Safe to call at any time, including from a collision handler mid-step
     pub fn remove_body(&mut self, entity: EntityId) {
-        self.bodies.remove(&entity);
-        self.colliders.remove(&entity);
+        if !self.pending_removals.contains(&entity) {
+            self.pending_removals.push(entity);
+        }
+        
+        if !self.stepping {
+            self.apply_pending_removals();
+        }
+    }
+    
+    pub fn add_collision_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&CollisionEvent, &mut PhysicsWorld) + Send + Sync + 'static,
+    {
+        self.collision_handlers.push(Box::new(handler));
     }
     
     pub fn get_body(&self, entity: EntityId) -> Option<&RigidBody> {
@@ -52,17 +165,86 @@ impl PhysicsWorld {
         self.bodies.get_mut(&entity)
     }
     
+    pub fn get_collider(&self, entity: EntityId) -> Option<&Collider> {
+        self.colliders.get(&entity)
+    }
+    
+    // Digest of every body's simulated state, visited in entity order
+    pub fn state_hash(&self) -> u64 {
+        let mut ids: Vec<EntityId> = self.bodies.keys().copied().collect();
+        ids.sort_unstable();
+        
+        let mut hasher = blake3::Hasher::new();
+        for id in ids {
+            hasher.update(&id.to_le_bytes());
+            hasher.update(&bincode::serialize(&self.bodies[&id]).expect("rigid body serializes to memory"));
+        }
+        
+        let digest = hasher.finalize();
+        u64::from_le_bytes(digest.as_bytes()[..8].try_into().unwrap())
+    }
+    
+    pub fn snapshot(&self) -> PhysicsSnapshot {
+        let mut bodies: Vec<(EntityId, RigidBody)> = self.bodies.iter().map(|(id, body)| (*id, body.clone())).collect();
+        bodies.sort_by_key(|(id, _)| *id);
+        let mut colliders: Vec<(EntityId, Collider)> = self.colliders.iter().map(|(id, collider)| (*id, collider.clone())).collect();
+        colliders.sort_by_key(|(id, _)| *id);
+        let mut sensors: Vec<EntityId> = self.sensors.iter().copied().collect();
+        sensors.sort_unstable();
+        let mut contacts: Vec<(EntityId, EntityId, f32)> = self.contact_cache.iter().map(|(&(a, b), &impulse)| (a, b, impulse)).collect();
+        contacts.sort_by_key(|&(a, b, _)| (a, b));
+        
+        PhysicsSnapshot {
+            config: self.config,
+            bodies,
+            colliders,
+            sensors,
+            contacts,
+            accumulator: self.accumulator,
+        }
+    }
+    
+    // Replaces the simulated state wholesale; last step's collisions and any
+    // removals still pending are dropped with it
+    pub fn restore(&mut self, snapshot: &PhysicsSnapshot) {
+        self.config = snapshot.config;
+        self.bodies = snapshot.bodies.iter().cloned().collect();
+        self.colliders = snapshot.colliders.iter().cloned().collect();
+        self.sensors = snapshot.sensors.iter().copied().collect();
+        self.contact_cache = snapshot.contacts.iter().map(|&(a, b, impulse)| ((a, b), impulse)).collect();
+        self.accumulator = snapshot.accumulator;
+        
+        self.collision_pairs.clear();
+        self.collision_events.clear();
+        self.pending_removals.clear();
+    }
+    
+    // Caps how many fixed steps one `step` call may run; whole steps past the
+    // cap are discarded rather than caught up on later
+    pub fn set_max_substeps(&mut self, max_substeps: u32) {
+        self.max_substeps = max_substeps;
+    }
+    
     pub fn step(&mut self, dt: f32) {
         self.accumulator += dt;
         
         // Fixed timestep for stable physics
+        let mut substeps = 0;
         while self.accumulator >= self.fixed_timestep {
+            if substeps == self.max_substeps {
+                self.accumulator %= self.fixed_timestep;
+                break;
+            }
+            
             self.fixed_update(self.fixed_timestep);
             self.accumulator -= self.fixed_timestep;
+            substeps += 1;
         }
     }
     
     fn fixed_update(&mut self, dt: f32) {
+        self.stepping = true;
+        
         // Clear previous frame's collision data
         self.collision_pairs.clear();
         self.collision_events.clear();
@@ -71,18 +253,21 @@ impl PhysicsWorld {
         for (entity, body) in &mut self.bodies {
             if body.body_type == BodyType::Dynamic {
                 // Apply gravity
-                body.apply_force(self.gravity * body.mass);
+                body.apply_force(self.config.gravity * body.mass);
                 
                 // Integrate forces to velocity
                 let acceleration = body.force / body.mass;
                 body.velocity += acceleration * dt;
                 
+                body.angular_velocity += (body.torque / body.inertia) * dt;
+                
                 // Apply damping
                 body.velocity *= 1.0 - body.linear_damping * dt;
                 body.angular_velocity *= 1.0 - body.angular_damping * dt;
                 
                 // Clear forces for next frame
                 body.force = Vec2::ZERO;
+                body.torque = 0.0;
             }
         }
         
@@ -95,37 +280,94 @@ impl PhysicsWorld {
         // Solve constraints
         self.solve_constraints();
         
-        // Integrate positions
+        // Integrate positions; kinematic bodies follow their velocity but are
+        // never pushed by forces or contacts
         for (entity, body) in &mut self.bodies {
-            if body.body_type == BodyType::Dynamic {
+            if body.body_type != BodyType::Static {
                 body.position += body.velocity * dt;
                 body.rotation += body.angular_velocity * dt;
             }
         }
+        
+        self.dispatch_collision_handlers();
+        
+        self.stepping = false;
+        self.apply_pending_removals();
     }
     
-    fn broad_phase(&mut self) {
-        // Simple O(n²) broad phase - in production, use spatial partitioning
-        let entities: Vec<EntityId> = self.colliders.keys().copied().collect();
+    fn dispatch_collision_handlers(&mut self) {
+        if self.collision_handlers.is_empty() {
+            return;
+        }
         
-        for i in 0..entities.len() {
-            for j in (i + 1)..entities.len() {
-                let entity_a = entities[i];
-                let entity_b = entities[j];
-                
-                // Skip if both are static
-                let body_a = self.bodies.get(&entity_a);
-                let body_b = self.bodies.get(&entity_b);
-                
-                if matches!((body_a, body_b), (Some(a), Some(b)) if a.body_type == BodyType::Static && b.body_type == BodyType::Static) {
-                    continue;
+        let mut handlers = std::mem::take(&mut self.collision_handlers);
+        let events = self.collision_events.clone();
+        
+        for event in &events {
+            for handler in &mut handlers {
+                // An earlier handler may already have removed one of the pair
+                if self.is_pending_removal(event.entity_a) || self.is_pending_removal(event.entity_b) {
+                    break;
                 }
-                
-                // Check AABB overlap
-                if let (Some(collider_a), Some(collider_b)) = (self.colliders.get(&entity_a), self.colliders.get(&entity_b)) {
-                    if self.aabb_overlap(entity_a, collider_a, entity_b, collider_b) {
-                        self.collision_pairs.push((entity_a, entity_b));
-                    }
+                handler(event, self);
+            }
+        }
+        
+        // Keep any handlers registered from inside a handler
+        handlers.append(&mut self.collision_handlers);
+        self.collision_handlers = handlers;
+    }
+    
+    fn is_pending_removal(&self, entity: EntityId) -> bool {
+        self.pending_removals.contains(&entity)
+    }
+    
+    fn apply_pending_removals(&mut self) {
+        if self.pending_removals.is_empty() {
+            return;
+        }
+        
+        let removed: HashSet<EntityId> = self.pending_removals.drain(..).collect();
+        
+        for entity in &removed {
+            self.bodies.remove(entity);
+            self.colliders.remove(entity);
+            self.sensors.remove(entity);
+        }
+        
+        // Drop anything that would still point at a removed entity
+        self.collision_pairs.retain(|(a, b)| !removed.contains(a) && !removed.contains(b));
+        self.collision_events.retain(|e| !removed.contains(&e.entity_a) && !removed.contains(&e.entity_b));
+        self.contact_cache.retain(|(a, b), _| !removed.contains(a) && !removed.contains(b));
+    }
+    
+    fn broad_phase(&mut self) {
+        self.spatial_hash.clear();
+        for (entity, collider) in &self.colliders {
+            let position = self.bodies.get(entity).map(|b| b.position).unwrap_or_default();
+            let (min, max) = collider.get_aabb(position);
+            self.spatial_hash.insert(*entity, min, max);
+        }
+        
+        // Candidates come back deduplicated and sorted; sharing a cell is not
+        // an overlap, so still confirm against the AABBs
+        for (entity_a, entity_b) in self.spatial_hash.candidate_pairs() {
+            // Skip if both are static
+            let body_a = self.bodies.get(&entity_a);
+            let body_b = self.bodies.get(&entity_b);
+            
+            if matches!((body_a, body_b), (Some(a), Some(b)) if a.body_type == BodyType::Static && b.body_type == BodyType::Static) {
+                continue;
+            }
+            
+            // Layers that don't interact never become candidates
+            if matches!((body_a, body_b), (Some(a), Some(b)) if !a.can_collide_with(b)) {
+                continue;
+            }
+            
+            if let (Some(collider_a), Some(collider_b)) = (self.colliders.get(&entity_a), self.colliders.get(&entity_b)) {
+                if self.aabb_overlap(entity_a, collider_a, entity_b, collider_b) {
+                    self.collision_pairs.push((entity_a, entity_b));
                 }
             }
         }
@@ -160,18 +402,20 @@ impl PhysicsWorld {
         let collider_a = self.colliders.get(&entity_a)?;
         let collider_b = self.colliders.get(&entity_b)?;
         
-        // Simple circle-circle collision for now
+        let (center_a, center_b) = (collider_a.center(body_a.position), collider_b.center(body_b.position));
+        
+        // Contact normals point from a towards b
         match (collider_a, collider_b) {
-            (Collider::Circle { radius: r1 }, Collider::Circle { radius: r2 }) => {
-                let distance = body_a.position.distance(body_b.position);
+            (Collider::Circle { radius: r1, .. }, Collider::Circle { radius: r2, .. }) => {
+                let distance = center_a.distance(center_b);
                 let radius_sum = r1 + r2;
                 
                 if distance < radius_sum {
-                    let normal = (body_b.position - body_a.position).normalize();
+                    let normal = (center_b - center_a).normalize();
                     let penetration = radius_sum - distance;
                     
                     Some(Contact {
-                        point: body_a.position + normal * r1,
+                        point: center_a + normal * r1,
                         normal,
                         penetration,
                     })
@@ -179,67 +423,182 @@ impl PhysicsWorld {
                     None
                 }
             }
-            _ => None, // Other collision types not implemented yet
+            (Collider::Box { half_extents: h1, .. }, Collider::Box { half_extents: h2, .. }) => {
+                box_box_contact(center_a, *h1, center_b, *h2)
+            }
+            (Collider::Circle { radius, .. }, Collider::Box { half_extents, .. }) => {
+                circle_box_contact(center_a, *radius, center_b, *half_extents)
+            }
+            (Collider::Box { half_extents, .. }, Collider::Circle { radius, .. }) => {
+                circle_box_contact(center_b, *radius, center_a, *half_extents)
+                    .map(|contact| Contact { normal: -contact.normal, ..contact })
+            }
+            // Anything involving a polygon goes through SAT
+            (Collider::Circle { radius, .. }, _) => {
+                circle_polygon_contact(center_a, *radius, &collider_b.polygon_points(body_b.position)?)
+            }
+            (_, Collider::Circle { radius, .. }) => {
+                circle_polygon_contact(center_b, *radius, &collider_a.polygon_points(body_a.position)?)
+                    .map(|contact| Contact { normal: -contact.normal, ..contact })
+            }
+            _ => polygon_polygon_contact(
+                &collider_a.polygon_points(body_a.position)?,
+                &collider_b.polygon_points(body_b.position)?,
+            ),
         }
     }
     
+    // Sequential impulses: each pass works from the velocities the previous
+    // contacts left behind, so a stack passes its weight all the way down
     fn solve_constraints(&mut self) {
-        // Simple impulse-based constraint solver
-        for event in &self.collision_events {
-            let (body_a, body_b) = match (self.bodies.get(&event.entity_a), self.bodies.get(&event.entity_b)) {
-                (Some(a), Some(b)) => (a.clone(), b.clone()),
-                _ => continue,
-            };
-            
-            // Skip if both static
-            if body_a.body_type == BodyType::Static && body_b.body_type == BodyType::Static {
+        let iterations = self.config.solver_iterations.max(1);
+        
+        let events = std::mem::take(&mut self.collision_events);
+        let mut impulses: Vec<ContactImpulse> = events.iter().map(|event| self.prepare_contact(event)).collect();
+        self.collision_events = events;
+        
+        for _ in 0..iterations {
+            self.solve_velocities(&mut impulses);
+        }
+        
+        // Rebuilt from this step's contacts alone, so pairs that came apart
+        // start from nothing if they touch again
+        self.contact_cache = self.collision_events.iter().zip(&impulses)
+            .filter(|(_, impulse)| impulse.normal > 0.0)
+            .map(|(event, impulse)| ((event.entity_a, event.entity_b), impulse.normal))
+            .collect();
+        
+        // Penetration is measured again on every pass, so a correction made
+        // lower in a stack is seen by the contacts above it
+        for _ in 0..iterations {
+            self.correct_positions();
+        }
+    }
+    
+    // The two bodies of a contact the solver pushes apart, or None for
+    // sensors and pairs where neither side can move
+    fn contact_bodies(&self, event: &CollisionEvent) -> Option<(RigidBody, RigidBody)> {
+        let (body_a, body_b) = (self.bodies.get(&event.entity_a)?, self.bodies.get(&event.entity_b)?);
+        
+        if body_a.body_type != BodyType::Dynamic && body_b.body_type != BodyType::Dynamic {
+            return None;
+        }
+        if self.sensors.contains(&event.entity_a) || self.sensors.contains(&event.entity_b) {
+            return None;
+        }
+        
+        Some((body_a.clone(), body_b.clone()))
+    }
+    
+    // Fixes the separating speed restitution aims for from the approach
+    // speed before any impulse, then applies the cached impulse if warm starting
+    fn prepare_contact(&mut self, event: &CollisionEvent) -> ContactImpulse {
+        let Some((body_a, body_b)) = self.contact_bodies(event) else {
+            return ContactImpulse::default();
+        };
+        
+        let normal = event.contact.normal;
+        let r_a = event.contact.point - body_a.position;
+        let r_b = event.contact.point - body_b.position;
+        
+        let approach = (velocity_at(&body_b, r_b) - velocity_at(&body_a, r_a)).dot(normal);
+        let restitution = (body_a.restitution + body_b.restitution) * 0.5;
+        let bounce = if approach < 0.0 { -restitution * approach } else { 0.0 };
+        
+        let cached = if self.config.warm_starting {
+            self.contact_cache.get(&(event.entity_a, event.entity_b)).copied().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        
+        if cached > 0.0 {
+            apply_impulse_at(self.bodies.get_mut(&event.entity_a), -normal * cached, r_a);
+            apply_impulse_at(self.bodies.get_mut(&event.entity_b), normal * cached, r_b);
+        }
+        
+        ContactImpulse { normal: cached, tangent: 0.0, bounce }
+    }
+    
+    // Impulses add up over the passes and only the running totals are
+    // clamped, so a later pass can take back part of what an earlier one
+    // (or the warm start) applied but the contact never pulls
+    fn solve_velocities(&mut self, impulses: &mut [ContactImpulse]) {
+        for (event, impulse) in self.collision_events.iter().zip(impulses.iter_mut()) {
+            let Some((body_a, body_b)) = self.contact_bodies(event) else {
                 continue;
-            }
-            
-            // Calculate relative velocity
-            let relative_velocity = body_b.velocity - body_a.velocity;
-            let velocity_along_normal = relative_velocity.dot(event.contact.normal);
+            };
             
-            // Don't resolve if velocities are separating
-            if velocity_along_normal > 0.0 {
-                continue;
-            }
+            let normal = event.contact.normal;
+            let r_a = event.contact.point - body_a.position;
+            let r_b = event.contact.point - body_b.position;
             
-            // Calculate impulse scalar
             let inv_mass_a = if body_a.body_type == BodyType::Dynamic { 1.0 / body_a.mass } else { 0.0 };
             let inv_mass_b = if body_b.body_type == BodyType::Dynamic { 1.0 / body_b.mass } else { 0.0 };
+            let inv_inertia_a = if body_a.body_type == BodyType::Dynamic { 1.0 / body_a.inertia } else { 0.0 };
+            let inv_inertia_b = if body_b.body_type == BodyType::Dynamic { 1.0 / body_b.inertia } else { 0.0 };
             
-            let restitution = (body_a.restitution + body_b.restitution) * 0.5;
-            let j = -(1.0 + restitution) * velocity_along_normal / (inv_mass_a + inv_mass_b);
+            // Effective mass along a direction, counting the lever arm of each contact
+            let effective = |dir: Vec2| {
+                inv_mass_a + inv_mass_b
+                    + r_a.cross(dir).powi(2) * inv_inertia_a
+                    + r_b.cross(dir).powi(2) * inv_inertia_b
+            };
             
-            let impulse = event.contact.normal * j;
+            let velocity_along_normal = (velocity_at(&body_b, r_b) - velocity_at(&body_a, r_a)).dot(normal);
+            let total = (impulse.normal + (impulse.bounce - velocity_along_normal) / effective(normal)).max(0.0);
+            let j = total - impulse.normal;
+            impulse.normal = total;
             
-            // Apply impulse
-            if let Some(body) = self.bodies.get_mut(&event.entity_a) {
-                if body.body_type == BodyType::Dynamic {
-                    body.velocity -= impulse * inv_mass_a;
-                }
+            apply_impulse_at(self.bodies.get_mut(&event.entity_a), -normal * j, r_a);
+            apply_impulse_at(self.bodies.get_mut(&event.entity_b), normal * j, r_b);
+            
+            // Friction works on the sliding left after the normal impulse,
+            // and its total can't exceed friction times the normal total
+            let relative_velocity = match (self.bodies.get(&event.entity_a), self.bodies.get(&event.entity_b)) {
+                (Some(a), Some(b)) => velocity_at(b, r_b) - velocity_at(a, r_a),
+                _ => continue,
+            };
+            let tangent = normal.perp();
+            let limit = (body_a.friction * body_b.friction).sqrt() * impulse.normal;
+            let total = (impulse.tangent - relative_velocity.dot(tangent) / effective(tangent)).clamp(-limit, limit);
+            let jt = total - impulse.tangent;
+            impulse.tangent = total;
+            
+            apply_impulse_at(self.bodies.get_mut(&event.entity_a), -tangent * jt, r_a);
+            apply_impulse_at(self.bodies.get_mut(&event.entity_b), tangent * jt, r_b);
+        }
+    }
+    
+    fn correct_positions(&mut self) {
+        for index in 0..self.collision_events.len() {
+            let (entity_a, entity_b) = (self.collision_events[index].entity_a, self.collision_events[index].entity_b);
+            if self.sensors.contains(&entity_a) || self.sensors.contains(&entity_b) {
+                continue;
             }
             
-            if let Some(body) = self.bodies.get_mut(&event.entity_b) {
-                if body.body_type == BodyType::Dynamic {
-                    body.velocity += impulse * inv_mass_b;
-                }
+            let inverse_mass = |body: Option<&RigidBody>| match body {
+                Some(body) if body.body_type == BodyType::Dynamic => 1.0 / body.mass,
+                _ => 0.0,
+            };
+            let inv_mass_a = inverse_mass(self.bodies.get(&entity_a));
+            let inv_mass_b = inverse_mass(self.bodies.get(&entity_b));
+            if inv_mass_a + inv_mass_b == 0.0 {
+                continue;
             }
             
-            // Position correction to prevent sinking
-            let percent = 0.2; // Penetration percentage to correct
-            let slop = 0.01; // Penetration allowance
-            let correction = event.contact.normal * 
-                ((event.contact.penetration - slop).max(0.0) / (inv_mass_a + inv_mass_b)) * percent;
+            let Some(contact) = self.check_collision(entity_a, entity_b) else {
+                continue;
+            };
+            let correction = contact.normal *
+                ((contact.penetration - self.config.slop).max(0.0) / (inv_mass_a + inv_mass_b)) * self.config.baumgarte;
             
-            if let Some(body) = self.bodies.get_mut(&event.entity_a) {
+            if let Some(body) = self.bodies.get_mut(&entity_a) {
                 if body.body_type == BodyType::Dynamic {
                     body.position -= correction * inv_mass_a;
                 }
             }
             
-            if let Some(body) = self.bodies.get_mut(&event.entity_b) {
+            if let Some(body) = self.bodies.get_mut(&entity_b) {
                 if body.body_type == BodyType::Dynamic {
                     body.position += correction * inv_mass_b;
                 }
@@ -247,6 +606,57 @@ impl PhysicsWorld {
         }
     }
     
+    // Every entity whose collider contains `point`, in id order
+    pub fn query_point(&self, point: Vec2) -> Vec<EntityId> {
+        let mut hits: Vec<EntityId> = self.colliders.iter()
+            .filter(|(entity, collider)| {
+                let position = self.bodies.get(entity).map(|b| b.position).unwrap_or_default();
+                collider.contains_point(position, point)
+            })
+            .map(|(&entity, _)| entity)
+            .collect();
+        
+        hits.sort_unstable();
+        hits
+    }
+    
+    // Nearest collider hit along the ray. A ray starting inside a shape hits it
+    // at distance 0, facing back along the ray
+    pub fn raycast(&self, origin: Vec2, dir: Vec2, max_dist: f32) -> Option<RayHit> {
+        let dir = dir.normalize();
+        if dir == Vec2::ZERO {
+            return None;
+        }
+        
+        let mut nearest: Option<RayHit> = None;
+        
+        for (&entity, collider) in &self.colliders {
+            let body_position = self.bodies.get(&entity).map(|b| b.position).unwrap_or_default();
+            let position = collider.center(body_position);
+            
+            let hit = match collider {
+                Collider::Circle { radius, .. } => ray_circle(origin, dir, position, *radius),
+                Collider::Box { half_extents, .. } => ray_box(origin, dir, position, *half_extents),
+                Collider::Polygon { .. } => collider.polygon_points(body_position)
+                    .and_then(|points| ray_polygon(origin, dir, &points)),
+            };
+            
+            if let Some((distance, normal)) = hit {
+                let closer = nearest.as_ref().is_none_or(|n| distance < n.distance);
+                if distance <= max_dist && closer {
+                    nearest = Some(RayHit {
+                        entity,
+                        point: origin + dir * distance,
+                        normal,
+                        distance,
+                    });
+                }
+            }
+        }
+        
+        nearest
+    }
+    
     pub fn get_collision_pairs(&self) -> &[(EntityId, EntityId)] {
         &self.collision_pairs
     }
@@ -254,4 +664,312 @@ impl PhysicsWorld {
     pub fn get_collision_events(&self) -> &[CollisionEvent] {
         &self.collision_events
     }
-}
\ No newline at end of file
+    
+    // Events from this step where at least one side is a sensor
+    pub fn trigger_events(&self) -> impl Iterator<Item = &CollisionEvent> {
+        self.collision_events
+            .iter()
+            .filter(|e| self.sensors.contains(&e.entity_a) || self.sensors.contains(&e.entity_b))
+    }
+}
+
+// Running totals for one contact over a step's solver passes
+#[derive(Debug, Clone, Copy, Default)]
+struct ContactImpulse {
+    normal: f32,
+    tangent: f32,
+    // Separating speed along the normal the solver aims for
+    bounce: f32,
+}
+
+// Velocity of the point at offset `r` from the body's center, including spin
+fn velocity_at(body: &RigidBody, r: Vec2) -> Vec2 {
+    body.velocity + r.perp() * body.angular_velocity
+}
+
+// Static and kinematic bodies have infinite mass and ignore impulses
+fn apply_impulse_at(body: Option<&mut RigidBody>, impulse: Vec2, r: Vec2) {
+    if let Some(body) = body {
+        if body.body_type == BodyType::Dynamic {
+            body.velocity += impulse / body.mass;
+            body.angular_velocity += r.cross(impulse) / body.inertia;
+        }
+    }
+}
+
+fn sign_or_one(v: f32) -> f32 {
+    if v < 0.0 { -1.0 } else { 1.0 }
+}
+
+// AABB vs AABB, resolved along the axis of least overlap
+fn box_box_contact(pos_a: Vec2, half_a: Vec2, pos_b: Vec2, half_b: Vec2) -> Option<Contact> {
+    let delta = pos_b - pos_a;
+    let overlap_x = half_a.x + half_b.x - delta.x.abs();
+    let overlap_y = half_a.y + half_b.y - delta.y.abs();
+    
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return None;
+    }
+    
+    // Middle of the overlapping region
+    let min = Vec2::new((pos_a.x - half_a.x).max(pos_b.x - half_b.x), (pos_a.y - half_a.y).max(pos_b.y - half_b.y));
+    let max = Vec2::new((pos_a.x + half_a.x).min(pos_b.x + half_b.x), (pos_a.y + half_a.y).min(pos_b.y + half_b.y));
+    let point = (min + max) * 0.5;
+    
+    let (normal, penetration) = if overlap_x < overlap_y {
+        (Vec2::new(sign_or_one(delta.x), 0.0), overlap_x)
+    } else {
+        (Vec2::new(0.0, sign_or_one(delta.y)), overlap_y)
+    };
+    
+    Some(Contact { point, normal, penetration })
+}
+
+// Circle (a) vs AABB (b): the closest point on the box is the circle centre
+// clamped to the box extents
+fn circle_box_contact(center: Vec2, radius: f32, box_pos: Vec2, half: Vec2) -> Option<Contact> {
+    let closest = Vec2::new(
+        center.x.clamp(box_pos.x - half.x, box_pos.x + half.x),
+        center.y.clamp(box_pos.y - half.y, box_pos.y + half.y),
+    );
+    let delta = closest - center;
+    let distance = delta.length();
+    
+    if distance > 0.0 {
+        if distance >= radius {
+            return None;
+        }
+        
+        return Some(Contact {
+            point: closest,
+            normal: delta / distance,
+            penetration: radius - distance,
+        });
+    }
+    
+    // Centre is inside the box: push out through the nearest face
+    let local = center - box_pos;
+    let to_face_x = half.x - local.x.abs();
+    let to_face_y = half.y - local.y.abs();
+    
+    let (normal, penetration) = if to_face_x < to_face_y {
+        (Vec2::new(-sign_or_one(local.x), 0.0), radius + to_face_x)
+    } else {
+        (Vec2::new(0.0, -sign_or_one(local.y)), radius + to_face_y)
+    };
+    
+    Some(Contact { point: center, normal, penetration })
+}
+
+// Edge normals of a convex polygon; the sign doesn't matter for SAT
+fn edge_normals(points: &[Vec2]) -> impl Iterator<Item = Vec2> + '_ {
+    (0..points.len())
+        .map(move |i| (points[(i + 1) % points.len()] - points[i]).perp().normalize())
+        .filter(|&normal| normal != Vec2::ZERO)
+}
+
+fn project(points: &[Vec2], axis: Vec2) -> (f32, f32) {
+    points.iter().fold((f32::MAX, f32::MIN), |(min, max), p| {
+        let d = p.dot(axis);
+        (min.min(d), max.max(d))
+    })
+}
+
+fn centroid(points: &[Vec2]) -> Vec2 {
+    points.iter().fold(Vec2::ZERO, |sum, &p| sum + p) / points.len() as f32
+}
+
+// Convex polygon vs convex polygon by the separating axis theorem. The
+// contact normal is the axis of least overlap, pointing from a to b
+fn polygon_polygon_contact(a: &[Vec2], b: &[Vec2]) -> Option<Contact> {
+    let mut best: Option<(Vec2, f32)> = None;
+    
+    for axis in edge_normals(a).chain(edge_normals(b)) {
+        let (min_a, max_a) = project(a, axis);
+        let (min_b, max_b) = project(b, axis);
+        let overlap = max_a.min(max_b) - min_a.max(min_b);
+        
+        if overlap <= 0.0 {
+            return None;
+        }
+        if best.filter(|&(_, least)| least <= overlap).is_none() {
+            best = Some((axis, overlap));
+        }
+    }
+    
+    let (mut normal, penetration) = best?;
+    if normal.dot(centroid(b) - centroid(a)) < 0.0 {
+        normal = -normal;
+    }
+    
+    // Average of the corners buried in the other shape; if none are (two
+    // edges crossing), b's deepest corner
+    let inside = |points: &[Vec2], other: &[Vec2]| -> Vec<Vec2> {
+        points.iter().copied()
+            .filter(|&p| edge_normals(other).all(|n| {
+                let (lo, hi) = project(other, n);
+                let d = p.dot(n);
+                d >= lo && d <= hi
+            }))
+            .collect()
+    };
+    let buried: Vec<Vec2> = inside(a, b).into_iter().chain(inside(b, a)).collect();
+    let point = if buried.is_empty() {
+        b.iter().copied()
+            .min_by(|p, q| p.dot(normal).total_cmp(&q.dot(normal)))
+            .unwrap_or_default()
+    } else {
+        centroid(&buried)
+    };
+    
+    Some(Contact { point, normal, penetration })
+}
+
+// Circle (a) vs convex polygon (b). Besides the edge normals, the axis
+// towards the polygon's nearest corner catches circles past a corner
+fn circle_polygon_contact(center: Vec2, radius: f32, polygon: &[Vec2]) -> Option<Contact> {
+    let nearest_corner = polygon.iter().copied()
+        .min_by(|p, q| p.distance(center).total_cmp(&q.distance(center)))?;
+    let corner_axis = (nearest_corner - center).normalize();
+    
+    let mut best: Option<(Vec2, f32)> = None;
+    for axis in edge_normals(polygon).chain(Some(corner_axis).filter(|&axis| axis != Vec2::ZERO)) {
+        let (min_p, max_p) = project(polygon, axis);
+        let c = center.dot(axis);
+        let overlap = max_p.min(c + radius) - min_p.max(c - radius);
+        
+        if overlap <= 0.0 {
+            return None;
+        }
+        if best.filter(|&(_, least)| least <= overlap).is_none() {
+            best = Some((axis, overlap));
+        }
+    }
+    
+    let (mut normal, penetration) = best?;
+    if normal.dot(centroid(polygon) - center) < 0.0 {
+        normal = -normal;
+    }
+    
+    // Deepest point of the polygon inside the circle
+    Some(Contact {
+        point: center + normal * (radius - penetration),
+        normal,
+        penetration,
+    })
+}
+
+// Returns (distance, normal) for a normalized `dir`
+fn ray_circle(origin: Vec2, dir: Vec2, center: Vec2, radius: f32) -> Option<(f32, Vec2)> {
+    let m = origin - center;
+    let b = m.dot(dir);
+    let c = m.dot(m) - radius * radius;
+    
+    if c <= 0.0 {
+        return Some((0.0, -dir));
+    }
+    
+    // Outside and pointing away
+    if b > 0.0 {
+        return None;
+    }
+    
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    
+    let distance = -b - discriminant.sqrt();
+    let normal = (origin + dir * distance - center).normalize();
+    Some((distance, normal))
+}
+
+// Slab test against an AABB
+fn ray_box(origin: Vec2, dir: Vec2, center: Vec2, half: Vec2) -> Option<(f32, Vec2)> {
+    let min = center - half;
+    let max = center + half;
+    
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+    
+    for (o, d, lo, hi, axis) in [
+        (origin.x, dir.x, min.x, max.x, Vec2::RIGHT),
+        (origin.y, dir.y, min.y, max.y, Vec2::UP),
+    ] {
+        if d.abs() < f32::EPSILON {
+            // Parallel to this slab: must already be between its planes
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+        
+        let (t1, t2) = ((lo - o) / d, (hi - o) / d);
+        let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+        
+        if near > t_enter {
+            t_enter = near;
+            normal = if d > 0.0 { -axis } else { axis };
+        }
+        t_exit = t_exit.min(far);
+    }
+    
+    if t_enter > t_exit || t_exit < 0.0 {
+        return None;
+    }
+    
+    if t_enter < 0.0 {
+        return Some((0.0, -dir));
+    }
+    
+    Some((t_enter, normal))
+}
+
+// Clips the ray against each edge's half-plane of a convex polygon, wound
+// either way
+fn ray_polygon(origin: Vec2, dir: Vec2, points: &[Vec2]) -> Option<(f32, Vec2)> {
+    let n = points.len();
+    let area: f32 = (0..n).map(|i| points[i].cross(points[(i + 1) % n])).sum();
+    let winding = sign_or_one(area);
+    
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+    
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let edge = b - a;
+        let outward = Vec2::new(edge.y, -edge.x).normalize() * winding;
+        
+        let facing = outward.dot(dir);
+        let gap = outward.dot(a - origin);
+        if facing.abs() < f32::EPSILON {
+            // Parallel to this edge: must already be on its inner side
+            if gap < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        
+        let t = gap / facing;
+        if facing < 0.0 {
+            if t > t_enter {
+                t_enter = t;
+                normal = outward;
+            }
+        } else {
+            t_exit = t_exit.min(t);
+        }
+    }
+    
+    if t_enter > t_exit || t_exit < 0.0 {
+        return None;
+    }
+    
+    if t_enter < 0.0 {
+        return Some((0.0, -dir));
+    }
+    
+    Some((t_enter, normal))
+}
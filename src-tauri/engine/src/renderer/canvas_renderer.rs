@@ -1,17 +1,103 @@
 // src-tauri/engine/src/renderer/canvas_renderer.rs
-use super::{Renderer, Sprite, RendererError};
+use super::{Renderer, Sprite, Material, Rect, Tilemap};
+use crate::ecs::EntityId;
 use crate::math::{Transform, Vec2};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 pub struct CanvasRenderer {
     frame_data: Vec<DrawCommand>,
+    // Stable id of each command in `frame_data`, see `command_id`
+    frame_ids: Vec<String>,
+    owner: Option<EntityId>,
+    // Commands drawn so far this frame per owner, numbering the next one
+    owner_counts: HashMap<Option<EntityId>, u32>,
+    encoding: FrameEncoding,
+    // Delta mode: what the frontend was last sent, and what changed since
+    delta: Option<DeltaState>,
     camera_position: Vec2,
     camera_zoom: f32,
     viewport_size: Vec2,
+    // World rect the view has to stay inside, as (min, max)
+    camera_bounds: Option<(Vec2, Vec2)>,
 }
 
-#[derive(Clone, Debug)]
-enum DrawCommand {
+// Bump whenever a DrawCommand variant is added, removed or reshaped, so an
+// older frontend can tell it is looking at a frame it can't fully draw
+pub const FRAME_SCHEMA_VERSION: u32 = 7;
+
+// Every command kind this version of the renderer can emit
+pub const FRAME_COMMAND_KINDS: &[&str] = &["Clear", "DrawSprite", "DrawRect", "DrawLine", "DrawCircle", "DrawText", "DrawTilemap"];
+
+// How `get_frame_data` packs a frame. JSON is easy to read in devtools;
+// binary is several times smaller and skips JSON.parse in the webview.
+//
+// Binary layout, little-endian. Strings are a u32 byte length then UTF-8;
+// vec2 is two f32s and color four:
+//   "DFRM", version u32, command count u32
+//   per command: tag u8 (index into FRAME_COMMAND_KINDS), then
+//     Clear       color
+//     DrawSprite  position, rotation f32, scale, texture_id, color,
+//                 flags u8 (1 flip_x, 2 flip_y, 4 has src), src x/y/w/h f32s
+//                 if flagged, pivot, layer i32, material,
+//                 uniform count u16 then (name, vec4) pairs
+//     DrawRect    position, size, color
+//     DrawLine    start, end, color, width f32
+//     DrawCircle  center, radius f32, color
+//     DrawText    position, text, size f32, color, font_id
+//     DrawTilemap texture_id, tile_size, layer i32, tile count u32,
+//                 then per tile position and src x/y/w/h f32s
+//
+// Delta frames (`set_delta_frames`) only carry what changed since the last
+// frame, keyed by command id: "e<entity>.<n>" for an entity's nth command,
+// "f<n>" for the nth command nobody owns. In JSON the frame gains
+// {"delta": {"full", "updates": [{"id", "command"}], "removed": [id], "order": [id]}}
+// in place of "commands"; `full` means drop everything held and start over.
+// "order" is every id in draw order and is only there when that order
+// differs from the last one sent (always on full frames); without it the
+// held order stands. Commands don't all carry a layer, so it can't be rebuilt
+// client-side.
+// Binary delta layout:
+//   "DFRD", version u32, full u8, update count u32,
+//   per update: id string then the command as above,
+//   removed count u32, then the removed id strings,
+//   has order u8, then if set an id count u32 and the ids
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrameEncoding {
+    #[default]
+    Json,
+    Binary,
+}
+
+pub const BINARY_FRAME_MAGIC: &[u8; 4] = b"DFRM";
+pub const BINARY_DELTA_MAGIC: &[u8; 4] = b"DFRD";
+
+#[derive(Default)]
+struct DeltaState {
+    sent: HashMap<String, DrawCommand>,
+    sent_order: Vec<String>,
+    frame: Delta,
+    force_full: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct Delta {
+    pub full: bool,
+    pub updates: Vec<DeltaUpdate>,
+    pub removed: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct DeltaUpdate {
+    pub id: String,
+    pub command: DrawCommand,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub(crate) enum DrawCommand {
     Clear { color: [f32; 4] },
     DrawSprite {
         position: Vec2,
@@ -21,6 +107,13 @@ enum DrawCommand {
         color: [f32; 4],
         flip_x: bool,
         flip_y: bool,
+        // Texel rect within the texture to draw; None draws all of it
+        src: Option<Rect>,
+        // Normalized point of the sprite placed at `position`
+        pivot: Vec2,
+        layer: i32,
+        material: String,
+        uniforms: BTreeMap<String, [f32; 4]>,
     },
     DrawRect {
         position: Vec2,
@@ -38,41 +131,539 @@ enum DrawCommand {
         radius: f32,
         color: [f32; 4],
     },
+    DrawText {
+        position: Vec2,
+        text: String,
+        size: f32,
+        color: [f32; 4],
+        font_id: String,
+    },
+    // A whole tilemap in one command; empty and off-screen tiles are left out
+    DrawTilemap {
+        texture_id: String,
+        tile_size: Vec2,
+        layer: i32,
+        tiles: Vec<TileDraw>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct TileDraw {
+    // Center of the tile
+    pub position: Vec2,
+    pub src: Rect,
+}
+
+impl DrawCommand {
+    fn kind(&self) -> &'static str {
+        match self {
+            DrawCommand::Clear { .. } => "Clear",
+            DrawCommand::DrawSprite { .. } => "DrawSprite",
+            DrawCommand::DrawRect { .. } => "DrawRect",
+            DrawCommand::DrawLine { .. } => "DrawLine",
+            DrawCommand::DrawCircle { .. } => "DrawCircle",
+            DrawCommand::DrawText { .. } => "DrawText",
+            DrawCommand::DrawTilemap { .. } => "DrawTilemap",
+        }
+    }
+    
+    // Sort key for end_frame. Clears stay in front of everything and shapes
+    // share the default sprite layer
+    fn layer(&self) -> i32 {
+        match self {
+            DrawCommand::Clear { .. } => i32::MIN,
+            DrawCommand::DrawSprite { layer, .. } | DrawCommand::DrawTilemap { layer, .. } => *layer,
+            _ => 0,
+        }
+    }
+    
+    fn encode(&self, out: &mut FrameWriter) {
+        let tag = FRAME_COMMAND_KINDS.iter().position(|&kind| kind == self.kind()).unwrap();
+        out.u8(tag as u8);
+        
+        match self {
+            DrawCommand::Clear { color } => out.color(color),
+            DrawCommand::DrawSprite {
+                position, rotation, scale, texture_id, color, flip_x, flip_y,
+                src, pivot, layer, material, uniforms,
+            } => {
+                out.vec2(*position);
+                out.f32(*rotation);
+                out.vec2(*scale);
+                out.str(texture_id);
+                out.color(color);
+                out.u8(*flip_x as u8 | (*flip_y as u8) << 1 | (src.is_some() as u8) << 2);
+                if let Some(src) = src {
+                    out.color(&[src.x, src.y, src.width, src.height]);
+                }
+                out.vec2(*pivot);
+                out.bytes(&layer.to_le_bytes());
+                out.str(material);
+                out.bytes(&(uniforms.len() as u16).to_le_bytes());
+                for (name, value) in uniforms {
+                    out.str(name);
+                    out.color(value);
+                }
+            }
+            DrawCommand::DrawRect { position, size, color } => {
+                out.vec2(*position);
+                out.vec2(*size);
+                out.color(color);
+            }
+            DrawCommand::DrawLine { start, end, color, width } => {
+                out.vec2(*start);
+                out.vec2(*end);
+                out.color(color);
+                out.f32(*width);
+            }
+            DrawCommand::DrawCircle { center, radius, color } => {
+                out.vec2(*center);
+                out.f32(*radius);
+                out.color(color);
+            }
+            DrawCommand::DrawText { position, text, size, color, font_id } => {
+                out.vec2(*position);
+                out.str(text);
+                out.f32(*size);
+                out.color(color);
+                out.str(font_id);
+            }
+            DrawCommand::DrawTilemap { texture_id, tile_size, layer, tiles } => {
+                out.str(texture_id);
+                out.vec2(*tile_size);
+                out.bytes(&layer.to_le_bytes());
+                out.bytes(&(tiles.len() as u32).to_le_bytes());
+                for tile in tiles {
+                    out.vec2(tile.position);
+                    out.color(&[tile.src.x, tile.src.y, tile.src.width, tile.src.height]);
+                }
+            }
+        }
+    }
+    
+    #[cfg(test)]
+    fn decode(input: &mut FrameReader) -> Option<Self> {
+        let command = match *FRAME_COMMAND_KINDS.get(input.u8()? as usize)? {
+            "Clear" => DrawCommand::Clear { color: input.color()? },
+            "DrawSprite" => {
+                let position = input.vec2()?;
+                let rotation = input.f32()?;
+                let scale = input.vec2()?;
+                let texture_id = input.string()?;
+                let color = input.color()?;
+                let flags = input.u8()?;
+                let src = match flags & 4 {
+                    0 => None,
+                    _ => {
+                        let [x, y, width, height] = input.color()?;
+                        Some(Rect { x, y, width, height })
+                    }
+                };
+                let pivot = input.vec2()?;
+                let layer = i32::from_le_bytes(input.take(4)?.try_into().ok()?);
+                let material = input.string()?;
+                let count = u16::from_le_bytes(input.take(2)?.try_into().ok()?);
+                let mut uniforms = BTreeMap::new();
+                for _ in 0..count {
+                    uniforms.insert(input.string()?, input.color()?);
+                }
+                
+                DrawCommand::DrawSprite {
+                    position, rotation, scale, texture_id, color,
+                    flip_x: flags & 1 != 0,
+                    flip_y: flags & 2 != 0,
+                    src, pivot, layer, material, uniforms,
+                }
+            }
+            "DrawRect" => DrawCommand::DrawRect {
+                position: input.vec2()?,
+                size: input.vec2()?,
+                color: input.color()?,
+            },
+            "DrawLine" => DrawCommand::DrawLine {
+                start: input.vec2()?,
+                end: input.vec2()?,
+                color: input.color()?,
+                width: input.f32()?,
+            },
+            "DrawCircle" => DrawCommand::DrawCircle {
+                center: input.vec2()?,
+                radius: input.f32()?,
+                color: input.color()?,
+            },
+            "DrawText" => DrawCommand::DrawText {
+                position: input.vec2()?,
+                text: input.string()?,
+                size: input.f32()?,
+                color: input.color()?,
+                font_id: input.string()?,
+            },
+            "DrawTilemap" => {
+                let texture_id = input.string()?;
+                let tile_size = input.vec2()?;
+                let layer = i32::from_le_bytes(input.take(4)?.try_into().ok()?);
+                let count = input.u32()?;
+                let mut tiles = Vec::new();
+                for _ in 0..count {
+                    let position = input.vec2()?;
+                    let [x, y, width, height] = input.color()?;
+                    tiles.push(TileDraw { position, src: Rect { x, y, width, height } });
+                }
+                
+                DrawCommand::DrawTilemap { texture_id, tile_size, layer, tiles }
+            }
+            _ => return None,
+        };
+        
+        Some(command)
+    }
+}
+
+struct FrameWriter(Vec<u8>);
+
+impl FrameWriter {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+    
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+    
+    fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_le_bytes());
+    }
+    
+    fn vec2(&mut self, value: Vec2) {
+        self.f32(value.x);
+        self.f32(value.y);
+    }
+    
+    fn color(&mut self, color: &[f32; 4]) {
+        for channel in color {
+            self.f32(*channel);
+        }
+    }
+    
+    fn str(&mut self, value: &str) {
+        self.bytes(&(value.len() as u32).to_le_bytes());
+        self.bytes(value.as_bytes());
+    }
+}
+
+// Decoding only exists to check the encoder; the frontend has its own
+#[cfg(test)]
+struct FrameReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+#[cfg(test)]
+impl<'a> FrameReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Some(bytes)
+    }
+    
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+    
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+    
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+    
+    fn vec2(&mut self) -> Option<Vec2> {
+        Some(Vec2::new(self.f32()?, self.f32()?))
+    }
+    
+    fn color(&mut self) -> Option<[f32; 4]> {
+        Some([self.f32()?, self.f32()?, self.f32()?, self.f32()?])
+    }
+    
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+// Reads a `FrameEncoding::Binary` frame back into its version and commands
+#[cfg(test)]
+pub(crate) fn decode_binary_frame(data: &[u8]) -> Option<(u32, Vec<DrawCommand>)> {
+    let mut input = FrameReader { data, pos: 0 };
+    if input.take(4)? != BINARY_FRAME_MAGIC {
+        return None;
+    }
+    
+    let version = input.u32()?;
+    let count = input.u32()?;
+    let commands = (0..count)
+        .map(|_| DrawCommand::decode(&mut input))
+        .collect::<Option<Vec<_>>>()?;
+    
+    // Trailing bytes mean the frame wasn't what we think it is
+    (input.pos == data.len()).then_some((version, commands))
+}
+
+// Reads a binary delta frame back into its version and delta
+#[cfg(test)]
+pub(crate) fn decode_binary_delta(data: &[u8]) -> Option<(u32, Delta)> {
+    let mut input = FrameReader { data, pos: 0 };
+    if input.take(4)? != BINARY_DELTA_MAGIC {
+        return None;
+    }
+    
+    let version = input.u32()?;
+    let full = input.u8()? != 0;
+    let updates = (0..input.u32()?)
+        .map(|_| Some(DeltaUpdate { id: input.string()?, command: DrawCommand::decode(&mut input)? }))
+        .collect::<Option<Vec<_>>>()?;
+    let removed = (0..input.u32()?)
+        .map(|_| input.string())
+        .collect::<Option<Vec<_>>>()?;
+    let order = match input.u8()? {
+        0 => None,
+        _ => Some((0..input.u32()?).map(|_| input.string()).collect::<Option<Vec<_>>>()?),
+    };
+    
+    (input.pos == data.len()).then_some((version, Delta { full, updates, removed, order }))
+}
+
+#[derive(Serialize)]
+struct Frame<'a> {
+    version: u32,
+    kinds: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commands: Option<&'a [DrawCommand]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delta: Option<&'a Delta>,
+}
+
+// The part of a frame a frontend reads before trusting the commands
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FrameHeader {
+    pub version: u32,
+    pub kinds: Vec<String>,
+}
+
+impl FrameHeader {
+    pub fn parse(frame: &[u8]) -> Option<Self> {
+        serde_json::from_slice(frame).ok()
+    }
+    
+    // Kinds in the frame that a decoder knowing only `known` would have to skip
+    pub fn unsupported_kinds(&self, known: &[&str]) -> Vec<&str> {
+        self.kinds.iter()
+            .map(String::as_str)
+            .filter(|kind| !known.contains(kind))
+            .collect()
+    }
 }
 
 impl CanvasRenderer {
     pub fn new() -> Self {
+        let viewport_size = Vec2::new(800.0, 600.0);
+        
         Self {
             frame_data: Vec::with_capacity(1000),
-            camera_position: Vec2::ZERO,
+            frame_ids: Vec::with_capacity(1000),
+            owner: None,
+            owner_counts: HashMap::new(),
+            encoding: FrameEncoding::default(),
+            delta: None,
+            // Centered so world and screen coordinates start out identical,
+            // which is how the frontend places sprites
+            camera_position: viewport_size * 0.5,
             camera_zoom: 1.0,
-            viewport_size: Vec2::new(800.0, 600.0),
+            viewport_size,
+            camera_bounds: None,
+        }
+    }
+    
+    pub fn frame_encoding(&self) -> FrameEncoding {
+        self.encoding
+    }
+    
+    pub fn delta_frames(&self) -> bool {
+        self.delta.is_some()
+    }
+    
+    fn command_id(owner: Option<EntityId>, n: u32) -> String {
+        match owner {
+            Some(entity) => format!("e{}.{}", entity, n),
+            None => format!("f{}", n),
+        }
+    }
+    
+    fn push(&mut self, command: DrawCommand) {
+        let count = self.owner_counts.entry(self.owner).or_insert(0);
+        self.frame_ids.push(Self::command_id(self.owner, *count));
+        *count += 1;
+        self.frame_data.push(command);
+    }
+    
+    fn encode_delta(&self, delta: &Delta) -> Vec<u8> {
+        if self.encoding == FrameEncoding::Binary {
+            let mut out = FrameWriter(Vec::with_capacity(17 + delta.updates.len() * 72));
+            out.bytes(BINARY_DELTA_MAGIC);
+            out.bytes(&FRAME_SCHEMA_VERSION.to_le_bytes());
+            out.u8(delta.full as u8);
+            out.bytes(&(delta.updates.len() as u32).to_le_bytes());
+            for update in &delta.updates {
+                out.str(&update.id);
+                update.command.encode(&mut out);
+            }
+            out.bytes(&(delta.removed.len() as u32).to_le_bytes());
+            for id in &delta.removed {
+                out.str(id);
+            }
+            out.u8(delta.order.is_some() as u8);
+            if let Some(order) = &delta.order {
+                out.bytes(&(order.len() as u32).to_le_bytes());
+                for id in order {
+                    out.str(id);
+                }
+            }
+            return out.0;
+        }
+        
+        let mut kinds = Vec::new();
+        for update in &delta.updates {
+            if !kinds.contains(&update.command.kind()) {
+                kinds.push(update.command.kind());
+            }
+        }
+        
+        serde_json::to_vec(&Frame {
+            version: FRAME_SCHEMA_VERSION,
+            kinds,
+            commands: None,
+            delta: Some(delta),
+        }).unwrap_or_default()
+    }
+    
+    // Diffs the finished frame against what was sent last time
+    fn build_delta(&mut self) {
+        let Some(state) = &mut self.delta else {
+            return;
+        };
+        
+        let full = std::mem::take(&mut state.force_full);
+        if full {
+            state.sent.clear();
+            state.sent_order.clear();
         }
+        
+        let mut updates = Vec::new();
+        let mut current = HashMap::with_capacity(self.frame_data.len());
+        for (id, command) in self.frame_ids.iter().zip(&self.frame_data) {
+            if state.sent.get(id) != Some(command) {
+                updates.push(DeltaUpdate { id: id.clone(), command: command.clone() });
+            }
+            current.insert(id.clone(), command.clone());
+        }
+        
+        let mut removed: Vec<String> = state.sent.keys()
+            .filter(|id| !current.contains_key(*id))
+            .cloned()
+            .collect();
+        removed.sort();
+        
+        let order = (full || self.frame_ids != state.sent_order).then(|| self.frame_ids.clone());
+        if let Some(order) = &order {
+            state.sent_order.clone_from(order);
+        }
+        
+        state.sent = current;
+        state.frame = Delta { full, updates, removed, order };
+    }
+    
+    pub fn set_camera_bounds(&mut self, min: Vec2, max: Vec2) {
+        self.camera_bounds = Some((min, max));
+        self.camera_position = self.clamp_camera(self.camera_position);
+    }
+    
+    pub fn clear_camera_bounds(&mut self) {
+        self.camera_bounds = None;
+    }
+    
+    // Visible world rect as (min, max)
+    pub fn view_rect(&self) -> (Vec2, Vec2) {
+        let half = self.viewport_size * (0.5 / self.camera_zoom);
+        (self.camera_position - half, self.camera_position + half)
+    }
+    
+    // Keeps the view inside the bounds; a world smaller than the view on
+    // some axis is centered on that axis instead
+    fn clamp_camera(&self, position: Vec2) -> Vec2 {
+        let Some((min, max)) = self.camera_bounds else {
+            return position;
+        };
+        
+        let half = self.viewport_size * (0.5 / self.camera_zoom);
+        let clamp_axis = |value: f32, min: f32, max: f32, half: f32| {
+            if max - min <= half * 2.0 {
+                (min + max) * 0.5
+            } else {
+                value.clamp(min + half, max - half)
+            }
+        };
+        
+        Vec2::new(
+            clamp_axis(position.x, min.x, max.x, half.x),
+            clamp_axis(position.y, min.y, max.y, half.y),
+        )
     }
 }
 
 impl Renderer for CanvasRenderer {
     fn begin_frame(&mut self) {
         self.frame_data.clear();
+        self.frame_ids.clear();
+        self.owner = None;
+        self.owner_counts.clear();
     }
     
     fn end_frame(&mut self) {
-        // Frame data is ready to be sent to the frontend
+        // Stable, so commands on the same layer keep their submission order
+        let mut commands: Vec<(String, DrawCommand)> = self.frame_ids.drain(..).zip(self.frame_data.drain(..)).collect();
+        commands.sort_by_key(|(_, command)| command.layer());
+        (self.frame_ids, self.frame_data) = commands.into_iter().unzip();
+        
+        self.build_delta();
     }
     
     fn clear(&mut self, color: [f32; 4]) {
-        self.frame_data.push(DrawCommand::Clear { color });
+        self.push(DrawCommand::Clear { color });
     }
     
-    fn draw_sprite(&mut self, sprite: &Sprite, transform: &Transform, interpolation: f32) {
+    fn draw_sprite(&mut self, sprite: &Sprite, material: &Material, transform: &Transform, previous: Option<&Transform>, interpolation: f32) {
+        let transform = previous.map_or(*transform, |previous| previous.interpolate(transform, interpolation));
+        
         // Convert 3D transform to 2D for top-down view
         let position = transform.position.xy();
         let scale = transform.scale.xy();
         
-        // For 2D, we only care about Z rotation
-        let rotation = transform.rotation.z.atan2(transform.rotation.w) * 2.0;
+        // Skip sprites whose bounds (position ± scale) miss the view entirely
+        let extent = Vec2::new(scale.x.abs(), scale.y.abs());
+        let (view_min, view_max) = self.view_rect();
+        if position.x + extent.x < view_min.x || position.x - extent.x > view_max.x
+            || position.y + extent.y < view_min.y || position.y - extent.y > view_max.y
+        {
+            return;
+        }
+        
+        // Taken from the rotation alone so a negative scale flips the sprite
+        // instead of also turning it half a turn
+        let rotation = transform.rotation.to_angle_2d();
         
-        self.frame_data.push(DrawCommand::DrawSprite {
+        self.push(DrawCommand::DrawSprite {
             position,
             rotation,
             scale,
@@ -80,11 +671,16 @@ impl Renderer for CanvasRenderer {
             color: sprite.color,
             flip_x: sprite.flip_x,
             flip_y: sprite.flip_y,
+            src: sprite.source_rect,
+            pivot: sprite.pivot,
+            layer: sprite.layer,
+            material: material.shader.clone(),
+            uniforms: material.uniforms.clone(),
         });
     }
     
     fn draw_rect(&mut self, position: Vec2, size: Vec2, color: [f32; 4]) {
-        self.frame_data.push(DrawCommand::DrawRect {
+        self.push(DrawCommand::DrawRect {
             position,
             size,
             color,
@@ -92,7 +688,7 @@ impl Renderer for CanvasRenderer {
     }
     
     fn draw_line(&mut self, start: Vec2, end: Vec2, color: [f32; 4], width: f32) {
-        self.frame_data.push(DrawCommand::DrawLine {
+        self.push(DrawCommand::DrawLine {
             start,
             end,
             color,
@@ -101,16 +697,50 @@ impl Renderer for CanvasRenderer {
     }
     
     fn draw_circle(&mut self, center: Vec2, radius: f32, color: [f32; 4]) {
-        self.frame_data.push(DrawCommand::DrawCircle {
+        self.push(DrawCommand::DrawCircle {
             center,
             radius,
             color,
         });
     }
     
+    fn draw_tilemap(&mut self, tilemap: &Tilemap, origin: Vec2) {
+        let half = tilemap.tile_size * 0.5;
+        let (view_min, view_max) = self.view_rect();
+        
+        let tiles: Vec<TileDraw> = tilemap.occupied()
+            .filter_map(|(x, y, tile)| {
+                let position = origin + tilemap.cell_center(x, y);
+                let visible = position.x + half.x >= view_min.x && position.x - half.x <= view_max.x
+                    && position.y + half.y >= view_min.y && position.y - half.y <= view_max.y;
+                visible.then_some(TileDraw { position, src: tilemap.source_rect(tile)? })
+            })
+            .collect();
+        if tiles.is_empty() {
+            return;
+        }
+        
+        self.push(DrawCommand::DrawTilemap {
+            texture_id: tilemap.tileset_texture_id.clone(),
+            tile_size: tilemap.tile_size,
+            layer: tilemap.layer,
+            tiles,
+        });
+    }
+    
+    fn draw_text(&mut self, position: Vec2, text: &str, size: f32, color: [f32; 4], font_id: &str) {
+        self.push(DrawCommand::DrawText {
+            position,
+            text: text.to_string(),
+            size,
+            color,
+            font_id: font_id.to_string(),
+        });
+    }
+    
     fn set_camera(&mut self, position: Vec2, zoom: f32) {
-        self.camera_position = position;
         self.camera_zoom = zoom;
+        self.camera_position = self.clamp_camera(position);
     }
     
     fn screen_to_world(&self, screen_pos: Vec2) -> Vec2 {
@@ -126,8 +756,52 @@ impl Renderer for CanvasRenderer {
     }
     
     fn get_frame_data(&self) -> Option<Vec<u8>> {
-        // Serialize frame data to send to frontend
-        // In a real implementation, you'd use a more efficient format
-        serde_json::to_vec(&self.frame_data).ok()
+        if let Some(state) = &self.delta {
+            return Some(self.encode_delta(&state.frame));
+        }
+        
+        if self.encoding == FrameEncoding::Binary {
+            let mut out = FrameWriter(Vec::with_capacity(12 + self.frame_data.len() * 64));
+            out.bytes(BINARY_FRAME_MAGIC);
+            out.bytes(&FRAME_SCHEMA_VERSION.to_le_bytes());
+            out.bytes(&(self.frame_data.len() as u32).to_le_bytes());
+            for command in &self.frame_data {
+                command.encode(&mut out);
+            }
+            return Some(out.0);
+        }
+        
+        let mut kinds = Vec::new();
+        for command in &self.frame_data {
+            if !kinds.contains(&command.kind()) {
+                kinds.push(command.kind());
+            }
+        }
+        
+        serde_json::to_vec(&Frame {
+            version: FRAME_SCHEMA_VERSION,
+            kinds,
+            commands: Some(&self.frame_data),
+            delta: None,
+        }).ok()
+    }
+    
+    fn set_command_owner(&mut self, owner: Option<EntityId>) {
+        self.owner = owner;
+    }
+    
+    fn set_frame_encoding(&mut self, encoding: FrameEncoding) {
+        self.encoding = encoding;
+    }
+    
+    // The first delta after switching on is always a full frame
+    fn set_delta_frames(&mut self, enabled: bool) {
+        self.delta = enabled.then(|| DeltaState { force_full: true, ..Default::default() });
+    }
+    
+    fn force_full_frame(&mut self) {
+        if let Some(state) = &mut self.delta {
+            state.force_full = true;
+        }
     }
 }
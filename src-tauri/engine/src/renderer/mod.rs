@@ -1,8 +1,10 @@
 // src-tauri/engine/src/renderer/mod.rs
 mod traits;
 mod canvas_renderer;
+#[cfg(feature = "wgpu-backend")]
 mod wgpu_renderer;
 
 pub use traits::*;
 pub use canvas_renderer::*;
+#[cfg(feature = "wgpu-backend")]
 pub use wgpu_renderer::*;
\ No newline at end of file
@@ -1,23 +1,57 @@
 // src-tauri/engine/src/renderer/traits.rs
+use std::collections::BTreeMap;
 use crate::math::{Transform, Vec2, Vec3};
 use serde::{Deserialize, Serialize};
-use crate::ecs::Component;
+use crate::ecs::{Component, EntityId};
+use super::FrameEncoding;
 
 pub trait Renderer: Send + Sync {
     fn begin_frame(&mut self);
     fn end_frame(&mut self);
     fn clear(&mut self, color: [f32; 4]);
     
-    fn draw_sprite(&mut self, sprite: &Sprite, transform: &Transform, interpolation: f32);
+    // With `previous`, the sprite is drawn `interpolation` of the way from it
+    // to `transform`
+    fn draw_sprite(&mut self, sprite: &Sprite, material: &Material, transform: &Transform, previous: Option<&Transform>, interpolation: f32);
     fn draw_rect(&mut self, position: Vec2, size: Vec2, color: [f32; 4]);
     fn draw_line(&mut self, start: Vec2, end: Vec2, color: [f32; 4], width: f32);
     fn draw_circle(&mut self, center: Vec2, radius: f32, color: [f32; 4]);
     
+    // Every non-empty tile, with `origin` as the map's top-left corner. This
+    // default draws each tile as its own sprite; backends that can batch
+    // override it
+    fn draw_tilemap(&mut self, tilemap: &Tilemap, origin: Vec2) {
+        let material = Material::default();
+        for (x, y, tile) in tilemap.occupied() {
+            let sprite = Sprite {
+                texture_id: tilemap.tileset_texture_id.clone(),
+                source_rect: tilemap.source_rect(tile),
+                layer: tilemap.layer,
+                ..Default::default()
+            };
+            let center = origin + tilemap.cell_center(x, y);
+            let transform = Transform::from_position(Vec3::new(center.x, center.y, 0.0));
+            self.draw_sprite(&sprite, &material, &transform, None, 1.0);
+        }
+    }
+    
+    // `font_id` names a BitmapFont asset; backends that can't draw text skip it
+    fn draw_text(&mut self, _position: Vec2, _text: &str, _size: f32, _color: [f32; 4], _font_id: &str) {}
+    
     fn set_camera(&mut self, position: Vec2, zoom: f32);
     fn screen_to_world(&self, screen_pos: Vec2) -> Vec2;
     fn world_to_screen(&self, world_pos: Vec2) -> Vec2;
     
     fn get_frame_data(&self) -> Option<Vec<u8>>;
+    
+    // Tags the commands drawn from here on with the entity they belong to, so
+    // renderers that diff frames can match them up from one frame to the next
+    fn set_command_owner(&mut self, _owner: Option<EntityId>) {}
+    
+    // Only meaningful for renderers that hand frames to a frontend
+    fn set_frame_encoding(&mut self, _encoding: FrameEncoding) {}
+    fn set_delta_frames(&mut self, _enabled: bool) {}
+    fn force_full_frame(&mut self) {}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +62,9 @@ pub struct Sprite {
     pub flip_y: bool,
     pub source_rect: Option<Rect>,
     pub pivot: Vec2,
+    // Draw order: lower layers are drawn first, so they end up underneath
+    #[serde(default)]
+    pub layer: i32,
 }
 
 impl Default for Sprite {
@@ -39,13 +76,146 @@ impl Default for Sprite {
             flip_y: false,
             source_rect: None,
             pivot: Vec2::new(0.5, 0.5),
+            layer: 0,
         }
     }
 }
 
 impl Component for Sprite {}
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+// A grid of tiles cut from one tileset texture, so a map is one entity rather
+// than one per tile. Tile ids count the tileset's cells left to right, top to
+// bottom, starting at 1; 0 leaves a cell empty. `tile_size` is both the cell
+// size in world units and the tile size in tileset texels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tilemap {
+    pub tile_size: Vec2,
+    pub width: u32,
+    pub height: u32,
+    // Row-major, `width * height` long
+    pub tiles: Vec<u32>,
+    pub tileset_texture_id: String,
+    // Tiles per row of the tileset texture
+    pub tileset_columns: u32,
+    #[serde(default)]
+    pub layer: i32,
+}
+
+impl Tilemap {
+    pub fn new(tileset_texture_id: impl Into<String>, tileset_columns: u32, width: u32, height: u32, tile_size: Vec2) -> Self {
+        Self {
+            tile_size,
+            width,
+            height,
+            tiles: vec![0; (width * height) as usize],
+            tileset_texture_id: tileset_texture_id.into(),
+            tileset_columns,
+            layer: 0,
+        }
+    }
+    
+    pub fn get(&self, x: u32, y: u32) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles.get((y * self.width + x) as usize).copied()
+    }
+    
+    // False if the cell is outside the map
+    pub fn set(&mut self, x: u32, y: u32, tile: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        match self.tiles.get_mut((y * self.width + x) as usize) {
+            Some(cell) => {
+                *cell = tile;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Non-empty cells as (column, row, tile id), row by row
+    pub fn occupied(&self) -> impl Iterator<Item = (u32, u32, u32)> + '_ {
+        let width = self.width.max(1);
+        self.tiles.iter()
+            .take((self.width * self.height) as usize)
+            .enumerate()
+            .filter(|(_, &tile)| tile != 0)
+            .map(move |(index, &tile)| (index as u32 % width, index as u32 / width, tile))
+    }
+    
+    // Texel rect of `tile` within the tileset
+    pub fn source_rect(&self, tile: u32) -> Option<Rect> {
+        if tile == 0 || self.tileset_columns == 0 {
+            return None;
+        }
+        
+        let index = tile - 1;
+        Some(Rect::new(
+            (index % self.tileset_columns) as f32 * self.tile_size.x,
+            (index / self.tileset_columns) as f32 * self.tile_size.y,
+            self.tile_size.x,
+            self.tile_size.y,
+        ))
+    }
+    
+    // Center of a cell relative to the map's top-left corner
+    pub fn cell_center(&self, x: u32, y: u32) -> Vec2 {
+        Vec2::new((x as f32 + 0.5) * self.tile_size.x, (y as f32 + 0.5) * self.tile_size.y)
+    }
+}
+
+impl Component for Tilemap {}
+
+// Selects the shader a sprite is drawn with. Uniforms are vec4s keyed by name
+// and uploaded in key order, which keeps them aligned for WGPU uniform buffers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub shader: String,
+    #[serde(default)]
+    pub uniforms: BTreeMap<String, [f32; 4]>,
+}
+
+impl Material {
+    pub const DEFAULT_SHADER: &'static str = "sprite";
+    
+    pub fn new(shader: impl Into<String>) -> Self {
+        Self {
+            shader: shader.into(),
+            uniforms: BTreeMap::new(),
+        }
+    }
+    
+    pub fn with_uniform(mut self, name: impl Into<String>, value: [f32; 4]) -> Self {
+        self.uniforms.insert(name.into(), value);
+        self
+    }
+    
+    pub fn with_float(self, name: impl Into<String>, value: f32) -> Self {
+        self.with_uniform(name, [value, 0.0, 0.0, 0.0])
+    }
+    
+    pub fn is_default(&self) -> bool {
+        self.shader == Self::DEFAULT_SHADER && self.uniforms.is_empty()
+    }
+    
+    // Flattened uniform block, one vec4 per entry in key order
+    pub fn uniform_data(&self) -> Vec<f32> {
+        self.uniforms.values().flatten().copied().collect()
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SHADER)
+    }
+}
+
+impl Component for Material {}
+
+// (x, y) is the min corner - the top-left in y-down screen and texture space
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -57,17 +227,78 @@ impl Rect {
     pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
         Self { x, y, width, height }
     }
+    
+    // Corners in either order
+    pub fn from_min_max(a: Vec2, b: Vec2) -> Self {
+        let min = Vec2::new(a.x.min(b.x), a.y.min(b.y));
+        let max = Vec2::new(a.x.max(b.x), a.y.max(b.y));
+        Self::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+    
+    pub fn min(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+    
+    pub fn max(&self) -> Vec2 {
+        Vec2::new(self.x + self.width, self.y + self.height)
+    }
+    
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.x + self.width * 0.5, self.y + self.height * 0.5)
+    }
+    
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+    
+    // Points on the edges count as inside
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.x && point.x <= self.x + self.width
+            && point.y >= self.y && point.y <= self.y + self.height
+    }
+    
+    // Rects that only share an edge or corner don't intersect
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width && other.x < self.x + self.width
+            && self.y < other.y + other.height && other.y < self.y + self.height
+    }
 }
 
-pub fn create_renderer(backend: RendererBackend) -> Result<Box<dyn Renderer>, RendererError> {
+// With `allow_fallback`, a WGPU backend that fails to initialize (no
+// adapter on headless CI, an old GPU) is swapped for the canvas renderer
+// instead of failing. Other errors always propagate
+pub fn create_renderer(backend: RendererBackend, allow_fallback: bool) -> Result<Box<dyn Renderer>, RendererError> {
+    create_renderer_with(backend, allow_fallback, create_wgpu_renderer)
+}
+
+pub(crate) fn create_renderer_with(
+    backend: RendererBackend,
+    allow_fallback: bool,
+    create_wgpu: impl FnOnce() -> Result<Box<dyn Renderer>, RendererError>,
+) -> Result<Box<dyn Renderer>, RendererError> {
     match backend {
         RendererBackend::Canvas => Ok(Box::new(CanvasRenderer::new())),
-        RendererBackend::Wgpu => {
-            // For now, fall back to canvas renderer
-            // WGPU implementation would be added later for native performance
-            Ok(Box::new(CanvasRenderer::new()))
-        }
+        RendererBackend::Wgpu => match create_wgpu() {
+            Err(RendererError::InitializationError(reason)) if allow_fallback => {
+                log::warn!("WGPU renderer unavailable ({}), falling back to canvas", reason);
+                Ok(Box::new(CanvasRenderer::new()))
+            }
+            result => result,
+        },
+    }
+}
+
+fn create_wgpu_renderer() -> Result<Box<dyn Renderer>, RendererError> {
+    #[cfg(feature = "wgpu-backend")]
+    {
+        // No window to attach to here, so render offscreen
+        let renderer = pollster::block_on(super::WgpuRenderer::new_headless(800, 600))?;
+        Ok(Box::new(renderer))
     }
+    #[cfg(not(feature = "wgpu-backend"))]
+    Err(RendererError::InitializationError(
+        "built without the wgpu-backend feature".to_string(),
+    ))
 }
 
 pub enum RendererBackend {
@@ -82,4 +313,10 @@ pub enum RendererError {
     
     #[error("Texture not found: {0}")]
     TextureNotFound(String),
+    
+    #[error("Invalid texture data: {0}")]
+    InvalidTexture(String),
+    
+    #[error("Shader compilation failed: {0}")]
+    ShaderError(String),
 }
\ No newline at end of file
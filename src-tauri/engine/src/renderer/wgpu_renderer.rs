@@ -1,64 +1,811 @@
 // src-tauri/engine/src/renderer/wgpu_renderer.rs
-use super::{Renderer, Sprite, RendererError};
-use crate::math::{Transform, Vec2};
+use super::{Renderer, Sprite, Material, RendererError};
+use crate::math::{Mat4, Transform, Vec2};
+use std::collections::HashMap;
+use std::ops::Range;
+use wgpu::util::DeviceExt;
+
+// Sprites whose texture hasn't been uploaded are drawn as a tinted square of
+// this size, the same placeholder the canvas frontend uses
+const PLACEHOLDER_SPRITE_SIZE: f32 = 64.0;
+const WHITE_TEXTURE: &str = "";
+const MAX_MATERIAL_UNIFORMS: usize = 16;
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+// Every pipeline shares this interface: the camera in group 0, the sprite
+// texture in group 1 and the material's vec4 uniforms in group 2. Custom
+// shaders registered with `register_shader` must keep the same entry points
+// and bindings
+const SPRITE_SHADER: &str = r#"
+struct Camera {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(1) @binding(0) var sprite_texture: texture_2d<f32>;
+@group(1) @binding(1) var sprite_sampler: sampler;
+
+struct Instance {
+    @location(0) position: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) pivot: vec2<f32>,
+    @location(3) rotation: f32,
+    @location(4) shape: f32,
+    @location(5) uv_rect: vec4<f32>,
+    @location(6) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) local: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) shape: f32,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, instance: Instance) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[index];
+    let offset = (corner - instance.pivot) * instance.size;
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let world = instance.position + vec2<f32>(offset.x * c - offset.y * s, offset.x * s + offset.y * c);
+    
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world, 0.0, 1.0);
+    out.uv = instance.uv_rect.xy + corner * instance.uv_rect.zw;
+    out.local = corner;
+    out.color = instance.color;
+    out.shape = instance.shape;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Circles are quads with everything outside the inscribed disc dropped
+    if (in.shape > 0.5 && length(in.local - vec2<f32>(0.5, 0.5)) > 0.5) {
+        discard;
+    }
+    return textureSample(sprite_texture, sprite_sampler, in.uv) * in.color;
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteInstance {
+    position: [f32; 2],
+    size: [f32; 2],
+    pivot: [f32; 2],
+    rotation: f32,
+    shape: f32,
+    uv_rect: [f32; 4],
+    color: [f32; 4],
+}
+
+impl SpriteInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32,
+        4 => Float32,
+        5 => Float32x4,
+        6 => Float32x4,
+    ];
+    
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+    
+    // A flat-colored quad drawn with the white texture
+    fn solid(position: Vec2, size: Vec2, pivot: Vec2, rotation: f32, color: [f32; 4]) -> Self {
+        Self {
+            position: [position.x, position.y],
+            size: [size.x, size.y],
+            pivot: [pivot.x, pivot.y],
+            rotation,
+            shape: 0.0,
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            color,
+        }
+    }
+}
+
+struct SpriteTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+impl SpriteTexture {
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.texture.width() as f32, self.texture.height() as f32)
+    }
+}
+
+// Consecutive instances sharing a pipeline, texture and uniform block go out
+// in a single instanced draw
+struct Batch {
+    pipeline: String,
+    texture: String,
+    uniforms: Vec<f32>,
+    instances: Range<u32>,
+}
+
+enum RenderTarget {
+    Surface {
+        surface: wgpu::Surface,
+        config: wgpu::SurfaceConfiguration,
+    },
+    Offscreen {
+        texture: wgpu::Texture,
+    },
+}
 
 pub struct WgpuRenderer {
-    // This would contain actual WGPU state
-    // For now, it's a placeholder
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    target: RenderTarget,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    
+    // One render pipeline per registered material shader
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: HashMap<String, wgpu::RenderPipeline>,
+    bound_pipeline: String,
+    uniform_staging: Vec<f32>,
+    
+    texture_layout: wgpu::BindGroupLayout,
+    material_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    textures: HashMap<String, SpriteTexture>,
+    
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    camera_position: Vec2,
+    camera_zoom: f32,
+    
+    instance_buffer: wgpu::Buffer,
+    instance_capacity: usize,
+    instances: Vec<SpriteInstance>,
+    batches: Vec<Batch>,
+    clear_color: [f32; 4],
 }
 
 impl WgpuRenderer {
-    pub async fn new() -> Result<Self, RendererError> {
-        // In a real implementation, this would initialize WGPU
-        Ok(Self {})
+    // Renders into `window`'s surface, which must outlive the renderer
+    pub async fn new<W>(window: &W, width: u32, height: u32) -> Result<Self, RendererError>
+    where
+        W: raw_window_handle::HasRawWindowHandle + raw_window_handle::HasRawDisplayHandle,
+    {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let surface = unsafe { instance.create_surface(window) }
+            .map_err(|e| RendererError::InitializationError(e.to_string()))?;
+        
+        Self::init(&instance, Some(surface), width, height).await
+    }
+    
+    // Renders into an offscreen texture; `get_frame_data` reads it back
+    pub async fn new_headless(width: u32, height: u32) -> Result<Self, RendererError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        Self::init(&instance, None, width, height).await
+    }
+    
+    async fn init(
+        instance: &wgpu::Instance,
+        surface: Option<wgpu::Surface>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, RendererError> {
+        let (width, height) = (width.max(1), height.max(1));
+        
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: surface.as_ref(),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| RendererError::InitializationError("no compatible GPU adapter".to_string()))?;
+        
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("dream-engine device"),
+                    features: wgpu::Features::empty(),
+                    limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+                },
+                None,
+            )
+            .await
+            .map_err(|e| RendererError::InitializationError(e.to_string()))?;
+        
+        let (target, format) = match surface {
+            Some(surface) => {
+                let capabilities = surface.get_capabilities(&adapter);
+                let format = capabilities.formats.iter()
+                    .copied()
+                    .find(|f| f.is_srgb())
+                    .or_else(|| capabilities.formats.first().copied())
+                    .ok_or_else(|| RendererError::InitializationError("surface has no supported formats".to_string()))?;
+                
+                let config = wgpu::SurfaceConfiguration {
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    format,
+                    width,
+                    height,
+                    present_mode: wgpu::PresentMode::Fifo,
+                    alpha_mode: capabilities.alpha_modes[0],
+                    view_formats: Vec::new(),
+                };
+                surface.configure(&device, &config);
+                
+                (RenderTarget::Surface { surface, config }, format)
+            }
+            None => {
+                let texture = create_offscreen_texture(&device, width, height);
+                (RenderTarget::Offscreen { texture }, OFFSCREEN_FORMAT)
+            }
+        };
+        
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("camera layout"),
+            entries: &[uniform_layout_entry(wgpu::ShaderStages::VERTEX)],
+        });
+        
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("sprite texture layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        
+        let material_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("material layout"),
+            entries: &[uniform_layout_entry(wgpu::ShaderStages::VERTEX_FRAGMENT)],
+        });
+        
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("sprite pipeline layout"),
+            bind_group_layouts: &[&camera_layout, &texture_layout, &material_layout],
+            push_constant_ranges: &[],
+        });
+        
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("camera uniform"),
+            size: std::mem::size_of::<[[f32; 4]; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("camera bind group"),
+            layout: &camera_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+        
+        // Pixel art stays crisp when zoomed
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("sprite sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        
+        let instance_capacity = 256;
+        let instance_buffer = create_instance_buffer(&device, instance_capacity);
+        
+        let mut renderer = Self {
+            device,
+            queue,
+            target,
+            format,
+            width,
+            height,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            bound_pipeline: Material::DEFAULT_SHADER.to_string(),
+            uniform_staging: Vec::new(),
+            texture_layout,
+            material_layout,
+            sampler,
+            textures: HashMap::new(),
+            camera_buffer,
+            camera_bind_group,
+            camera_position: Vec2::ZERO,
+            camera_zoom: 1.0,
+            instance_buffer,
+            instance_capacity,
+            instances: Vec::new(),
+            batches: Vec::new(),
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        };
+        
+        renderer.register_shader(Material::DEFAULT_SHADER, SPRITE_SHADER)?;
+        renderer.upload_texture(WHITE_TEXTURE, 1, 1, &[255, 255, 255, 255])?;
+        
+        Ok(renderer)
+    }
+    
+    // Compiles `wgsl` into a render pipeline materials can select by name
+    pub fn register_shader(&mut self, name: impl Into<String>, wgsl: impl Into<String>) -> Result<(), RendererError> {
+        let name = name.into();
+        let wgsl = wgsl.into();
+        
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        
+        let module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&name),
+            source: wgpu::ShaderSource::Wgsl(wgsl.into()),
+        });
+        
+        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&name),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[SpriteInstance::layout()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: self.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        
+        if let Some(error) = pollster::block_on(self.device.pop_error_scope()) {
+            return Err(RendererError::ShaderError(format!("{}: {}", name, error)));
+        }
+        
+        self.pipelines.insert(name, pipeline);
+        Ok(())
+    }
+    
+    // Uploads tightly packed RGBA8 pixels; sprites reference them by `id`
+    pub fn upload_texture(&mut self, id: impl Into<String>, width: u32, height: u32, rgba: &[u8]) -> Result<(), RendererError> {
+        let id = id.into();
+        if width == 0 || height == 0 || rgba.len() != (width * height * 4) as usize {
+            return Err(RendererError::InvalidTexture(format!(
+                "{}: expected {}x{} RGBA8 pixels, got {} bytes",
+                id, width, height, rgba.len()
+            )));
+        }
+        
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &wgpu::TextureDescriptor {
+                label: Some(&id),
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            rgba,
+        );
+        
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&id),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+        
+        self.textures.insert(id, SpriteTexture { texture, bind_group });
+        Ok(())
+    }
+    
+    pub fn has_texture(&self, id: &str) -> bool {
+        self.textures.contains_key(id)
+    }
+    
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        self.width = width;
+        self.height = height;
+        
+        match &mut self.target {
+            RenderTarget::Surface { surface, config } => {
+                config.width = width;
+                config.height = height;
+                surface.configure(&self.device, config);
+            }
+            RenderTarget::Offscreen { texture } => {
+                *texture = create_offscreen_texture(&self.device, width, height);
+            }
+        }
+    }
+    
+    // Copies the offscreen target back as tightly packed RGBA8 rows. Surface
+    // renderers present directly and have nothing to read
+    pub fn read_pixels(&self) -> Option<Vec<u8>> {
+        let RenderTarget::Offscreen { texture } = &self.target else {
+            return None;
+        };
+        
+        let unpadded_row = self.width * 4;
+        let padded_row = unpadded_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: (padded_row * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("readback"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+        
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().ok()?.ok()?;
+        
+        let mapped = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((unpadded_row * self.height) as usize);
+        for row in mapped.chunks(padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row as usize]);
+        }
+        drop(mapped);
+        buffer.unmap();
+        
+        Some(pixels)
+    }
+    
+    // Unknown shaders fall back to the default sprite pipeline
+    fn bind_material(&mut self, material: &Material) {
+        let pipeline = if self.pipelines.contains_key(&material.shader) {
+            material.shader.as_str()
+        } else {
+            Material::DEFAULT_SHADER
+        };
+        
+        if self.bound_pipeline != pipeline {
+            self.bound_pipeline = pipeline.to_string();
+        }
+        
+        self.uniform_staging.clear();
+        self.uniform_staging.extend(material.uniform_data());
+        self.uniform_staging.truncate(MAX_MATERIAL_UNIFORMS * 4);
+    }
+    
+    // Queues an instance, extending the last batch when its state matches
+    fn push_instance(&mut self, texture: &str, instance: SpriteInstance) {
+        let index = self.instances.len() as u32;
+        self.instances.push(instance);
+        
+        if let Some(batch) = self.batches.last_mut() {
+            if batch.pipeline == self.bound_pipeline && batch.texture == texture && batch.uniforms == self.uniform_staging {
+                batch.instances.end = index + 1;
+                return;
+            }
+        }
+        
+        self.batches.push(Batch {
+            pipeline: self.bound_pipeline.clone(),
+            texture: texture.to_string(),
+            uniforms: self.uniform_staging.clone(),
+            instances: index..index + 1,
+        });
+    }
+    
+    fn push_solid(&mut self, instance: SpriteInstance) {
+        self.bound_pipeline = Material::DEFAULT_SHADER.to_string();
+        self.uniform_staging.clear();
+        self.push_instance(WHITE_TEXTURE, instance);
+    }
+    
+    // Same mapping as the canvas renderer: the camera sits at the viewport
+    // center, y grows downward and `zoom` is pixels per world unit
+    fn view_projection(&self) -> Mat4 {
+        let sx = 2.0 * self.camera_zoom / self.width as f32;
+        let sy = 2.0 * self.camera_zoom / self.height as f32;
+        let camera = self.camera_position;
+        
+        Mat4::from_cols([
+            [sx, 0.0, 0.0, 0.0],
+            [0.0, -sy, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [-sx * camera.x, sy * camera.y, 0.0, 1.0],
+        ])
+    }
+    
+    fn upload_instances(&mut self) {
+        if self.instances.len() > self.instance_capacity {
+            self.instance_capacity = self.instances.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(&self.device, self.instance_capacity);
+        }
+        self.queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&self.instances));
+    }
+    
+    fn material_bind_group(&self, uniforms: &[f32]) -> wgpu::BindGroup {
+        let mut block = [0.0f32; MAX_MATERIAL_UNIFORMS * 4];
+        block[..uniforms.len()].copy_from_slice(uniforms);
+        
+        let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("material uniforms"),
+            contents: bytemuck::cast_slice(&block),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("material bind group"),
+            layout: &self.material_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        })
     }
 }
 
 impl Renderer for WgpuRenderer {
     fn begin_frame(&mut self) {
-        // Begin WGPU frame
+        self.instances.clear();
+        self.batches.clear();
     }
     
     fn end_frame(&mut self) {
-        // Submit WGPU commands
+        let frame = match &self.target {
+            RenderTarget::Surface { surface, config } => match surface.get_current_texture() {
+                Ok(frame) => Some(frame),
+                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                    surface.configure(&self.device, config);
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("Skipping frame: {}", e);
+                    return;
+                }
+            },
+            RenderTarget::Offscreen { .. } => None,
+        };
+        
+        let view = match (&frame, &self.target) {
+            (Some(frame), _) => frame.texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, RenderTarget::Offscreen { texture }) => texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (None, RenderTarget::Surface { .. }) => return,
+        };
+        
+        let view_projection = self.view_projection();
+        self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&view_projection.cols));
+        self.upload_instances();
+        
+        let material_groups: Vec<_> = self.batches.iter()
+            .map(|batch| self.material_bind_group(&batch.uniforms))
+            .collect();
+        
+        let [r, g, b, a] = self.clear_color;
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("sprite frame"),
+        });
+        
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("sprite pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r: r as f64, g: g as f64, b: b as f64, a: a as f64 }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            
+            pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+            
+            for (batch, material_group) in self.batches.iter().zip(&material_groups) {
+                let pipeline = &self.pipelines[&batch.pipeline];
+                let texture = &self.textures[&batch.texture];
+                
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(1, &texture.bind_group, &[]);
+                pass.set_bind_group(2, material_group, &[]);
+                pass.draw(0..6, batch.instances.clone());
+            }
+        }
+        
+        self.queue.submit(Some(encoder.finish()));
+        
+        if let Some(frame) = frame {
+            frame.present();
+        }
     }
     
+    // Clearing discards anything queued earlier in the frame, as it would on
+    // the canvas
     fn clear(&mut self, color: [f32; 4]) {
-        // Clear with WGPU
+        self.clear_color = color;
+        self.instances.clear();
+        self.batches.clear();
     }
     
-    fn draw_sprite(&mut self, sprite: &Sprite, transform: &Transform, interpolation: f32) {
-        // Draw sprite with WGPU
+    fn draw_sprite(&mut self, sprite: &Sprite, material: &Material, transform: &Transform, previous: Option<&Transform>, interpolation: f32) {
+        let transform = &previous.map_or(*transform, |previous| previous.interpolate(transform, interpolation));
+        self.bind_material(material);
+        
+        let (texture_id, texture_size) = match self.textures.get(&sprite.texture_id) {
+            Some(texture) if !sprite.texture_id.is_empty() => (sprite.texture_id.as_str(), texture.size()),
+            _ => (WHITE_TEXTURE, Vec2::splat(PLACEHOLDER_SPRITE_SIZE)),
+        };
+        
+        // Source rects are in texel coordinates; the quad takes their size
+        let (mut uv_rect, base_size) = match (&sprite.source_rect, texture_id) {
+            (Some(rect), id) if id != WHITE_TEXTURE => (
+                [
+                    rect.x / texture_size.x,
+                    rect.y / texture_size.y,
+                    rect.width / texture_size.x,
+                    rect.height / texture_size.y,
+                ],
+                Vec2::new(rect.width, rect.height),
+            ),
+            _ => ([0.0, 0.0, 1.0, 1.0], texture_size),
+        };
+        
+        if sprite.flip_x {
+            uv_rect[0] += uv_rect[2];
+            uv_rect[2] = -uv_rect[2];
+        }
+        if sprite.flip_y {
+            uv_rect[1] += uv_rect[3];
+            uv_rect[3] = -uv_rect[3];
+        }
+        
+        // Scale stays out of it, as in the canvas renderer
+        let rotation = transform.rotation.to_angle_2d();
+        let size = base_size * transform.scale.xy();
+        
+        let texture_id = texture_id.to_string();
+        self.push_instance(&texture_id, SpriteInstance {
+            position: [transform.position.x, transform.position.y],
+            size: [size.x, size.y],
+            pivot: [sprite.pivot.x, sprite.pivot.y],
+            rotation,
+            shape: 0.0,
+            uv_rect,
+            color: sprite.color,
+        });
     }
     
     fn draw_rect(&mut self, position: Vec2, size: Vec2, color: [f32; 4]) {
-        // Draw rect with WGPU
+        self.push_solid(SpriteInstance::solid(position, size, Vec2::ZERO, 0.0, color));
     }
     
     fn draw_line(&mut self, start: Vec2, end: Vec2, color: [f32; 4], width: f32) {
-        // Draw line with WGPU
+        let delta = end - start;
+        let rotation = delta.y.atan2(delta.x);
+        let size = Vec2::new(delta.length(), width);
+        self.push_solid(SpriteInstance::solid(start, size, Vec2::new(0.0, 0.5), rotation, color));
     }
     
     fn draw_circle(&mut self, center: Vec2, radius: f32, color: [f32; 4]) {
-        // Draw circle with WGPU
+        let mut instance = SpriteInstance::solid(center, Vec2::splat(radius * 2.0), Vec2::splat(0.5), 0.0, color);
+        instance.shape = 1.0;
+        self.push_solid(instance);
     }
     
     fn set_camera(&mut self, position: Vec2, zoom: f32) {
-        // Update WGPU view matrix
+        self.camera_position = position;
+        self.camera_zoom = zoom;
     }
     
     fn screen_to_world(&self, screen_pos: Vec2) -> Vec2 {
-        // Transform screen to world coordinates
-        screen_pos
+        let viewport = Vec2::new(self.width as f32, self.height as f32);
+        let centered = screen_pos - viewport * 0.5;
+        centered / self.camera_zoom + self.camera_position
     }
     
     fn world_to_screen(&self, world_pos: Vec2) -> Vec2 {
-        // Transform world to screen coordinates
-        world_pos
+        let viewport = Vec2::new(self.width as f32, self.height as f32);
+        (world_pos - self.camera_position) * self.camera_zoom + viewport * 0.5
     }
     
     fn get_frame_data(&self) -> Option<Vec<u8>> {
-        // WGPU would render directly to a surface
-        None
+        // Surface renderers present directly; headless ones hand back pixels
+        self.read_pixels()
     }
-}
\ No newline at end of file
+}
+
+fn uniform_layout_entry(visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+fn create_offscreen_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("offscreen target"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: OFFSCREEN_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+fn create_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("sprite instances"),
+        size: (capacity * std::mem::size_of::<SpriteInstance>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
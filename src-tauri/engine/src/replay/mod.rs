@@ -0,0 +1,6 @@
+// src-tauri/engine/src/replay/mod.rs
+mod recording;
+mod rng;
+
+pub use recording::*;
+pub use rng::*;
@@ -0,0 +1,128 @@
+// src-tauri/engine/src/replay/recording.rs
+use serde::{Deserialize, Serialize};
+use crate::input::InputSnapshot;
+use crate::EngineError;
+
+pub const REPLAY_VERSION: u32 = 1;
+
+// Fixed steps between recorded world hashes
+pub const CHECKPOINT_INTERVAL: u64 = 30;
+
+// World hash after `step` fixed steps; step 0 is the state recording began in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub step: u64,
+    pub hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub fixed_timestep: f32,
+    // One input snapshot per fixed step
+    pub frames: Vec<InputSnapshot>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl Replay {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("replay serializes to memory")
+    }
+    
+    pub fn from_bytes(data: &[u8]) -> Result<Self, EngineError> {
+        let replay: Replay = bincode::deserialize(data)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(EngineError::InvalidReplay(format!(
+                "unsupported replay version {}", replay.version
+            )));
+        }
+        Ok(replay)
+    }
+    
+    pub fn final_checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoints.last().copied()
+    }
+}
+
+// Playback stopped because the world stopped matching the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayDesync {
+    pub step: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+pub(crate) struct Recorder {
+    replay: Replay,
+}
+
+impl Recorder {
+    pub fn new(seed: u64, fixed_timestep: f32, initial_hash: u64) -> Self {
+        Self {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                seed,
+                fixed_timestep,
+                frames: Vec::new(),
+                checkpoints: vec![Checkpoint { step: 0, hash: initial_hash }],
+            },
+        }
+    }
+    
+    // Returns the number of steps recorded so far
+    pub fn record_step(&mut self, input: InputSnapshot) -> u64 {
+        self.replay.frames.push(input);
+        self.replay.frames.len() as u64
+    }
+    
+    pub fn wants_checkpoint(&self, step: u64) -> bool {
+        step.is_multiple_of(CHECKPOINT_INTERVAL)
+    }
+    
+    pub fn checkpoint(&mut self, step: u64, hash: u64) {
+        self.replay.checkpoints.push(Checkpoint { step, hash });
+    }
+    
+    // Always ends on a checkpoint so playback can confirm the final state
+    pub fn finish(mut self, final_hash: u64) -> Replay {
+        let steps = self.replay.frames.len() as u64;
+        if self.replay.final_checkpoint().map(|c| c.step) != Some(steps) {
+            self.checkpoint(steps, final_hash);
+        }
+        self.replay
+    }
+}
+
+pub(crate) struct Player {
+    replay: Replay,
+    step: u64,
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, step: 0 }
+    }
+    
+    // Input for the upcoming step, or None once the recording is used up
+    pub fn next_input(&mut self) -> Option<&InputSnapshot> {
+        let frame = self.replay.frames.get(self.step as usize)?;
+        self.step += 1;
+        Some(frame)
+    }
+    
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+    
+    pub fn expected_hash(&self, step: u64) -> Option<u64> {
+        self.replay.checkpoints
+            .binary_search_by_key(&step, |c| c.step)
+            .ok()
+            .map(|i| self.replay.checkpoints[i].hash)
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.step as usize >= self.replay.frames.len()
+    }
+}
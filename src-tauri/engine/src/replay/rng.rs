@@ -0,0 +1,55 @@
+// src-tauri/engine/src/replay/rng.rs
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+// The engine's random source, kept as a world resource. Systems should draw
+// from this instead of `thread_rng` so recordings replay identically
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+    
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+    
+    // Uniform in [0, 1), built from the top 24 bits so every value is exact
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+    
+    // Uniform in [min, max)
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+    
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+    
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+    
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
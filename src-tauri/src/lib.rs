@@ -19,6 +19,8 @@ use dream_engine::tauri_integration::{
     create_preview_engine,
     update_preview_scene,
     render_preview_frame,
+    send_input_event,
+    pick_entity,
     destroy_preview_engine,
     compile_visual_script,
 };
@@ -34,6 +36,8 @@ pub fn run() {
             create_preview_engine,
             update_preview_scene,
             render_preview_frame,
+            send_input_event,
+            pick_entity,
             destroy_preview_engine,
             compile_visual_script,
             // Project management
@@ -111,54 +115,148 @@ async fn load_project(project_id: String) -> Result<serde_json::Value, String> {
         .map_err(|e| format!("Failed to parse project file: {}", e))
 }
 
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+// Editors autosave on every keystroke, so saves are debounced per file: each
+// call waits out SAVE_DEBOUNCE and only the newest call in the window writes.
+// Writes go to a temp file that is renamed over project.json, so readers never
+// see a partially written project.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+struct SaveSlot {
+    generation: u64,
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+fn save_slots() -> &'static Mutex<HashMap<PathBuf, SaveSlot>> {
+    static SLOTS: OnceLock<Mutex<HashMap<PathBuf, SaveSlot>>> = OnceLock::new();
+    SLOTS.get_or_init(Default::default)
+}
+
+// The generation is claimed before the returned future is polled, so calls
+// coalesce in the order they were made rather than the order they get scheduled
+fn save_json_debounced(
+    path: PathBuf,
+    data: serde_json::Value,
+    window: Duration,
+) -> impl std::future::Future<Output = Result<(), String>> {
+    let (generation, write_lock) = {
+        let mut slots = save_slots().lock().unwrap();
+        let slot = slots.entry(path.clone()).or_insert_with(|| SaveSlot {
+            generation: 0,
+            write_lock: Default::default(),
+        });
+        slot.generation += 1;
+        (slot.generation, slot.write_lock.clone())
+    };
+    
+    async move {
+        tokio::time::sleep(window).await;
+        
+        let is_latest = |slots: &HashMap<PathBuf, SaveSlot>| {
+            slots.get(&path).is_some_and(|slot| slot.generation == generation)
+        };
+        
+        if !is_latest(&save_slots().lock().unwrap()) {
+            // A newer save superseded this one and will write instead
+            return Ok(());
+        }
+        
+        let _guard = write_lock.lock().await;
+        
+        // Re-check after waiting for any in-flight writer
+        if !is_latest(&save_slots().lock().unwrap()) {
+            return Ok(());
+        }
+        
+        let contents = serde_json::to_string_pretty(&data)
+            .map_err(|e| format!("Failed to serialize project: {}", e))?;
+        write_atomic(&path, contents.as_bytes()).await
+    }
+}
+
+async fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+    
+    let tmp_path = path.with_extension("json.tmp");
+    
+    let mut file = tokio::fs::File::create(&tmp_path).await
+        .map_err(|e| format!("Failed to save project: {}", e))?;
+    file.write_all(contents).await
+        .map_err(|e| format!("Failed to save project: {}", e))?;
+    file.sync_all().await
+        .map_err(|e| format!("Failed to save project: {}", e))?;
+    drop(file);
+    
+    tokio::fs::rename(&tmp_path, path).await
+        .map_err(|e| format!("Failed to save project: {}", e))
+}
+
 #[tauri::command]
 async fn save_project(project_id: String, data: serde_json::Value) -> Result<(), String> {
-    use std::fs;
-    
     let projects_dir = tauri::api::path::app_data_dir(&tauri::Config::default())
         .ok_or("Failed to get app data directory")?
         .join("projects");
     
     let project_file = projects_dir.join(&project_id).join("project.json");
     
-    fs::write(
-        project_file,
-        serde_json::to_string_pretty(&data).unwrap()
-    ).map_err(|e| format!("Failed to save project: {}", e))?;
-    
-    Ok(())
+    save_json_debounced(project_file, data, SAVE_DEBOUNCE).await
 }
 
 #[tauri::command]
 async fn import_asset(project_id: String, asset_path: String, asset_type: String) -> Result<String, String> {
-    use std::fs;
-    use std::path::Path;
-    
     let source_path = Path::new(&asset_path);
     if !source_path.exists() {
         return Err("Asset file not found".to_string());
     }
     
-    let asset_id = uuid::Uuid::new_v4().to_string();
-    let file_name = source_path.file_name()
-        .ok_or("Invalid file path")?
-        .to_string_lossy();
-    
     let projects_dir = tauri::api::path::app_data_dir(&tauri::Config::default())
         .ok_or("Failed to get app data directory")?
         .join("projects");
     
-    let asset_dir = projects_dir
-        .join(&project_id)
-        .join("assets")
-        .join(&asset_type);
+    import_asset_file(&projects_dir.join(&project_id).join("assets"), source_path, &asset_type)
+}
+
+// Copies `source_path` into `assets_dir/<asset_type>/` next to a `.meta`
+// holding its content hash. Importing bytes the project already has returns
+// the existing asset's id instead of storing a second copy
+fn import_asset_file(assets_dir: &Path, source_path: &Path, asset_type: &str) -> Result<String, String> {
+    use std::fs;
+    
+    let data = fs::read(source_path)
+        .map_err(|e| format!("Failed to read asset: {}", e))?;
+    let hash = blake3::hash(&data).to_hex().to_string();
+    
+    if let Some(existing_id) = find_asset_by_hash(assets_dir, &hash) {
+        return Ok(existing_id);
+    }
+    
+    let asset_id = uuid::Uuid::new_v4().to_string();
+    let file_name = source_path.file_name()
+        .ok_or("Invalid file path")?
+        .to_string_lossy()
+        .to_string();
     
+    let asset_dir = assets_dir.join(asset_type);
     fs::create_dir_all(&asset_dir)
         .map_err(|e| format!("Failed to create asset directory: {}", e))?;
     
-    let dest_path = asset_dir.join(&file_name);
+    // Different contents under a name already taken get the hash appended
+    let mut stored_name = file_name.clone();
+    if asset_dir.join(&stored_name).exists() {
+        let name = Path::new(&file_name);
+        let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+        stored_name = match name.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, &hash[..8], ext.to_string_lossy()),
+            None => format!("{}-{}", stem, &hash[..8]),
+        };
+    }
+    let dest_path = asset_dir.join(&stored_name);
     
-    fs::copy(source_path, &dest_path)
+    fs::write(&dest_path, &data)
         .map_err(|e| format!("Failed to copy asset: {}", e))?;
     
     // Create asset metadata
@@ -167,16 +265,37 @@ async fn import_asset(project_id: String, asset_path: String, asset_type: String
         "name": file_name,
         "type": asset_type,
         "path": dest_path.to_string_lossy(),
+        "hash": hash,
         "importedAt": chrono::Utc::now().to_rfc3339(),
     });
     
-    let meta_path = asset_dir.join(format!("{}.meta", file_name));
+    let meta_path = asset_dir.join(format!("{}.meta", stored_name));
     fs::write(meta_path, serde_json::to_string_pretty(&asset_meta).unwrap())
         .map_err(|e| format!("Failed to write asset metadata: {}", e))?;
     
     Ok(asset_id)
 }
 
+// Metas written before hashes were recorded are hashed from their file
+fn find_asset_by_hash(assets_dir: &Path, hash: &str) -> Option<String> {
+    use walkdir::WalkDir;
+    
+    WalkDir::new(assets_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "meta"))
+        .filter_map(|entry| {
+            let meta: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(entry.path()).ok()?).ok()?;
+            let stored_hash = match meta["hash"].as_str() {
+                Some(stored_hash) => stored_hash.to_string(),
+                None => blake3::hash(&std::fs::read(meta["path"].as_str()?).ok()?).to_hex().to_string(),
+            };
+            
+            meta["id"].as_str().filter(|_| stored_hash == hash).map(str::to_string)
+        })
+        .next()
+}
+
 #[tauri::command]
 async fn get_project_assets(project_id: String) -> Result<Vec<serde_json::Value>, String> {
     use std::fs;
@@ -259,3 +378,138 @@ async fn export_game(project_id: String, output_path: String) -> Result<(), Stri
 }
 
 // Also add to Cargo.toml dependencies:
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Scratch directory under the system temp dir, removed again on drop so
+    // a failed assertion doesn't leave it behind
+    struct TempDir {
+        path: PathBuf,
+    }
+    
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("dream_{}_{}", name, std::process::id()));
+            std::fs::create_dir_all(&path).unwrap();
+            Self { path }
+        }
+    }
+    
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+    
+    // The directory goes away once the returned `TempDir` is dropped
+    fn temp_project_file(name: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new(&format!("save_{}", name));
+        let path = dir.path.join("project.json");
+        (dir, path)
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_rapid_saves_coalesce_to_last() {
+        let (_dir, path) = temp_project_file("coalesce");
+        
+        // Build every future first so the call order is fixed, then race them
+        let saves: Vec<_> = (0..50)
+            .map(|i| save_json_debounced(path.clone(), serde_json::json!({ "revision": i }), Duration::from_millis(50)))
+            .collect();
+        let handles: Vec<_> = saves.into_iter().map(tokio::spawn).collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["revision"], 49);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_saves_are_never_observed_partially_written() {
+        let (_dir, path) = temp_project_file("atomic");
+        std::fs::write(&path, "{}").unwrap();
+        
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = {
+            let (path, done) = (path.clone(), done.clone());
+            std::thread::spawn(move || {
+                let mut reads = 0;
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    let contents = std::fs::read_to_string(&path).unwrap();
+                    serde_json::from_str::<serde_json::Value>(&contents)
+                        .expect("project file was observed truncated");
+                    reads += 1;
+                }
+                reads
+            })
+        };
+        
+        // Large payloads, spaced out past the window so each one really writes
+        let payload = "x".repeat(256 * 1024);
+        for i in 0..5 {
+            save_json_debounced(path.clone(), serde_json::json!({ "revision": i, "blob": payload }), Duration::from_millis(5))
+                .await
+                .unwrap();
+        }
+        
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+        assert!(reader.join().unwrap() > 0);
+        
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["revision"], 4);
+    }
+    
+    fn temp_import_dirs(name: &str) -> (TempDir, PathBuf, PathBuf) {
+        let root = TempDir::new(&format!("import_{}", name));
+        let (sources, assets_dir) = (root.path.join("sources"), root.path.join("assets"));
+        std::fs::create_dir_all(&sources).unwrap();
+        (root, sources, assets_dir)
+    }
+    
+    fn stored_files(assets_dir: &Path) -> Vec<PathBuf> {
+        walkdir::WalkDir::new(assets_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.path().extension().filter(|&ext| ext == "meta").is_none())
+            .map(|e| e.path().to_path_buf())
+            .collect()
+    }
+    
+    #[test]
+    fn test_importing_same_bytes_twice_reuses_asset() {
+        let (_root, sources, assets_dir) = temp_import_dirs("dedup");
+        std::fs::write(sources.join("hero.png"), b"same pixels").unwrap();
+        std::fs::write(sources.join("hero_copy.png"), b"same pixels").unwrap();
+        
+        let first = import_asset_file(&assets_dir, &sources.join("hero.png"), "texture").unwrap();
+        let second = import_asset_file(&assets_dir, &sources.join("hero_copy.png"), "texture").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(stored_files(&assets_dir).len(), 1);
+        
+        let meta: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(assets_dir.join("texture/hero.png.meta")).unwrap()
+        ).unwrap();
+        assert_eq!(meta["hash"], blake3::hash(b"same pixels").to_hex().to_string());
+    }
+    
+    #[test]
+    fn test_importing_different_bytes_stores_both() {
+        let (_root, sources, assets_dir) = temp_import_dirs("distinct");
+        std::fs::create_dir_all(sources.join("v2")).unwrap();
+        std::fs::write(sources.join("hero.png"), b"first pixels").unwrap();
+        std::fs::write(sources.join("v2/hero.png"), b"second pixels").unwrap();
+        
+        let first = import_asset_file(&assets_dir, &sources.join("hero.png"), "texture").unwrap();
+        let second = import_asset_file(&assets_dir, &sources.join("v2/hero.png"), "texture").unwrap();
+        assert_ne!(first, second);
+        
+        // Same file name, so the second copy can't overwrite the first
+        let mut contents: Vec<Vec<u8>> = stored_files(&assets_dir).iter().map(|path| std::fs::read(path).unwrap()).collect();
+        contents.sort();
+        assert_eq!(contents, vec![b"first pixels".to_vec(), b"second pixels".to_vec()]);
+    }
+}
@@ -3,21 +3,46 @@ import { useEffect, useRef, useState, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import * as PIXI from 'pixi.js';
 
+// Must match FRAME_SCHEMA_VERSION in the engine's canvas renderer
+const FRAME_SCHEMA_VERSION = 7;
+const SUPPORTED_KINDS = ['Clear', 'DrawSprite', 'DrawRect', 'DrawLine', 'DrawCircle', 'DrawText', 'DrawTilemap'];
+
 interface EngineFrame {
+  version: number;
+  kinds: string[];
   commands: DrawCommand[];
 }
 
 interface DrawCommand {
-  type: 'Clear' | 'DrawSprite' | 'DrawRect' | 'DrawLine' | 'DrawCircle';
+  type: string;
   data: any;
 }
 
+const warnedKinds = new Set<string>();
+
+// Newer engines may emit commands this build can't draw; skip those instead of misparsing
+function checkFrameSchema(frame: EngineFrame) {
+  if (frame.version > FRAME_SCHEMA_VERSION && !warnedKinds.has('@version')) {
+    warnedKinds.add('@version');
+    console.warn(`Engine frame schema v${frame.version} is newer than supported v${FRAME_SCHEMA_VERSION}`);
+  }
+  
+  for (const kind of frame.kinds ?? []) {
+    if (!SUPPORTED_KINDS.includes(kind) && !warnedKinds.has(kind)) {
+      warnedKinds.add(kind);
+      console.warn(`Skipping unsupported draw command: ${kind}`);
+    }
+  }
+}
+
 export function useGameEngine(projectId: string) {
   const [engineId, setEngineId] = useState<string | null>(null);
   const [isRunning, setIsRunning] = useState(false);
   const pixiApp = useRef<PIXI.Application | null>(null);
   const sprites = useRef<Map<string, PIXI.Sprite>>(new Map());
   const graphics = useRef<PIXI.Graphics | null>(null);
+  const texts = useRef<PIXI.Container | null>(null);
+  const tiles = useRef<PIXI.Container | null>(null);
   
   // Initialize engine
   useEffect(() => {
@@ -58,10 +83,20 @@ export function useGameEngine(projectId: string) {
     
     pixiApp.current = app;
     
+    // Tilemaps are rebuilt every frame, underneath everything else
+    const tl = new PIXI.Container();
+    app.stage.addChild(tl);
+    tiles.current = tl;
+    
     // Create graphics object for primitives
     const g = new PIXI.Graphics();
     app.stage.addChild(g);
     graphics.current = g;
+    
+    // Text is rebuilt every frame, above sprites and primitives
+    const t = new PIXI.Container();
+    app.stage.addChild(t);
+    texts.current = t;
   }, []);
   
   // Start/stop game loop
@@ -113,8 +148,12 @@ export function useGameEngine(projectId: string) {
   const renderFrame = useCallback((frame: EngineFrame) => {
     if (!pixiApp.current || !graphics.current) return;
     
+    checkFrameSchema(frame);
+    
     // Clear graphics
     graphics.current.clear();
+    texts.current?.removeChildren().forEach(child => child.destroy());
+    tiles.current?.removeChildren().forEach(child => child.destroy());
     
     for (const command of frame.commands) {
       switch (command.type) {
@@ -146,33 +185,85 @@ export function useGameEngine(projectId: string) {
           graphics.current.drawCircle(center.x, center.y, radius);
           graphics.current.endFill();
           break;
+          
+        case 'DrawText':
+          renderText(command.data);
+          break;
+          
+        case 'DrawTilemap':
+          renderTilemap(command.data);
+          break;
       }
     }
   }, []);
   
+  // Bitmap fonts are used once loaded into PIXI; until then fall back to a system font
+  const renderText = useCallback((data: any) => {
+    if (!texts.current) return;
+    
+    const { position, text, size, color, font_id } = data;
+    const label = PIXI.BitmapFont.available[font_id]
+      ? new PIXI.BitmapText(text, { fontName: font_id, fontSize: size, tint: rgbToHex(color) })
+      : new PIXI.Text(text, { fontFamily: 'monospace', fontSize: size, fill: rgbToHex(color) });
+    
+    label.position.set(position.x, position.y);
+    label.alpha = color[3];
+    texts.current.addChild(label);
+  }, []);
+  
+  // One sprite per visible tile, cut from the tileset by its src rect
+  const renderTilemap = useCallback((data: any) => {
+    if (!tiles.current) return;
+    
+    const { texture_id, tiles: cells } = data;
+    const tileset = PIXI.utils.TextureCache[texture_id] as PIXI.Texture | undefined;
+    if (!tileset) return;
+    
+    for (const { position, src } of cells) {
+      const tile = new PIXI.Sprite(
+        new PIXI.Texture(tileset.baseTexture, new PIXI.Rectangle(src.x, src.y, src.width, src.height))
+      );
+      tile.anchor.set(0.5, 0.5);
+      tile.position.set(position.x, position.y);
+      tiles.current.addChild(tile);
+    }
+  }, []);
+  
   const renderSprite = useCallback((data: any) => {
     if (!pixiApp.current) return;
     
-    const { position, rotation, scale, texture_id, color, flip_x, flip_y } = data;
+    const { position, rotation, scale, texture_id, color, flip_x, flip_y, src, pivot } = data;
+    
+    // Atlas frames of one texture are distinct sprites
+    const key = src ? `${texture_id}#${src.x},${src.y},${src.width},${src.height}` : texture_id;
     
     // Get or create sprite
-    let sprite = sprites.current.get(texture_id);
+    let sprite = sprites.current.get(key);
     if (!sprite) {
-      // For now, create a placeholder rectangle
-      const graphics = new PIXI.Graphics();
-      graphics.beginFill(0xffffff);
-      graphics.drawRect(-32, -32, 64, 64);
-      graphics.endFill();
+      const loaded = PIXI.utils.TextureCache[texture_id] as PIXI.Texture | undefined;
+      let texture: PIXI.Texture;
+      
+      if (loaded) {
+        texture = src
+          ? new PIXI.Texture(loaded.baseTexture, new PIXI.Rectangle(src.x, src.y, src.width, src.height))
+          : loaded;
+      } else {
+        // For now, create a placeholder rectangle
+        const graphics = new PIXI.Graphics();
+        graphics.beginFill(0xffffff);
+        graphics.drawRect(-32, -32, 64, 64);
+        graphics.endFill();
+        texture = pixiApp.current.renderer.generateTexture(graphics);
+      }
       
-      const texture = pixiApp.current.renderer.generateTexture(graphics);
       sprite = new PIXI.Sprite(texture);
-      sprite.anchor.set(0.5);
       
       pixiApp.current.stage.addChild(sprite);
-      sprites.current.set(texture_id, sprite);
+      sprites.current.set(key, sprite);
     }
     
     // Update sprite transform
+    sprite.anchor.set(pivot?.x ?? 0.5, pivot?.y ?? 0.5);
     sprite.position.set(position.x, position.y);
     sprite.rotation = rotation;
     sprite.scale.set(scale.x * (flip_x ? -1 : 1), scale.y * (flip_y ? -1 : 1));
@@ -194,6 +285,32 @@ export function useGameEngine(projectId: string) {
     }
   }, [engineId]);
   
+  // Forward a device event; mouse coordinates are canvas pixels
+  const sendInput = useCallback(async (event: EngineInputEvent) => {
+    if (!engineId) return;
+    
+    try {
+      await invoke('send_input_event', {
+        engineId,
+        eventJson: JSON.stringify(event)
+      });
+    } catch (error) {
+      console.error('Failed to send input:', error);
+    }
+  }, [engineId]);
+  
+  // Engine id of the entity under a canvas pixel, or null over empty space
+  const pickEntity = useCallback(async (screenX: number, screenY: number) => {
+    if (!engineId) return null;
+    
+    try {
+      return await invoke<number | null>('pick_entity', { engineId, screenX, screenY });
+    } catch (error) {
+      console.error('Failed to pick entity:', error);
+      return null;
+    }
+  }, [engineId]);
+  
   // Compile visual script
   const compileScript = useCallback(async (script: any) => {
     try {
@@ -202,8 +319,9 @@ export function useGameEngine(projectId: string) {
       });
       return result;
     } catch (error) {
-      console.error('Failed to compile script:', error);
-      throw error;
+      const detail = parseCompileError(error);
+      console.error('Failed to compile script:', detail.message);
+      throw detail;
     }
   }, []);
   
@@ -214,6 +332,8 @@ export function useGameEngine(projectId: string) {
     start,
     stop,
     updateScene,
+    sendInput,
+    pickEntity,
     compileScript,
   };
 }
@@ -224,4 +344,32 @@ function rgbToHex(color: number[]): number {
   const g = Math.floor(color[1] * 255);
   const b = Math.floor(color[2] * 255);
   return (r << 16) | (g << 8) | b;
-}
\ No newline at end of file
+}
+
+// Mirrors the engine's `InputEvent`; keys use `KeyboardEvent.code`
+export type EngineInputEvent =
+  | { type: 'KeyDown'; code: string }
+  | { type: 'KeyUp'; code: string }
+  | { type: 'MouseMove'; x: number; y: number }
+  | { type: 'MouseDown'; button: number }
+  | { type: 'MouseUp'; button: number }
+  | { type: 'Axis'; name: string; value: number };
+
+export interface CompileError {
+  message: string;
+  nodeId: string | null;
+  position: [number, number] | null;
+}
+
+// The engine reports which node failed so the editor can outline it
+function parseCompileError(error: unknown): CompileError {
+  try {
+    const parsed = JSON.parse(String(error));
+    if (parsed && typeof parsed.message === 'string') {
+      return parsed;
+    }
+  } catch {
+    // Not a structured compiler error
+  }
+  return { message: String(error), nodeId: null, position: null };
+}
@@ -67,6 +67,9 @@ export interface VisualScript {
   name: string;
   nodes: VisualScriptNode[];
   connections: VisualScriptConnection[];
+  debug_only?: boolean;
+  // Ids of scripts that must run before this one
+  run_after?: string[];
 }
 
 export interface VisualScriptNode {